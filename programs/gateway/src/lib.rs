@@ -9,7 +9,7 @@ mod utils;
 pub use contexts::*;
 pub use errors::*;
 pub use state::*;
-pub use utils::DEPOSIT_FEE;
+pub use utils::{recover_eth_address, DEPOSIT_FEE, MAX_DEPOSIT_PAYLOAD_SIZE};
 
 // Define the program ID
 #[cfg(feature = "dev")]
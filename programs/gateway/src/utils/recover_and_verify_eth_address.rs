@@ -6,23 +6,33 @@ use anchor_lang::solana_program::secp256k1_recover::secp256k1_recover;
 use crate::errors::Errors;
 use crate::state::Pda;
 
-/// Recovers and verifies eth address from signature.
-pub fn recover_and_verify_eth_address(
-    pda: &mut Account<Pda>,
+/// Recovers the eth address that signed `message_hash`.
+pub fn recover_eth_address(
     message_hash: &[u8; 32],
     recovery_id: u8,
     signature: &[u8; 64],
-) -> Result<()> {
+) -> Result<[u8; 20]> {
     let pubkey = secp256k1_recover(message_hash, recovery_id, signature)
         .map_err(|_| ProgramError::InvalidArgument)?;
 
     // pubkey is 64 Bytes, uncompressed public secp256k1 public key
     let h = hash(pubkey.to_bytes().as_slice()).to_bytes();
     let address = &h.as_slice()[12..32]; // ethereum address is the last 20 Bytes of the hashed pubkey
-    msg!("Recovered address {:?}", address);
 
     let mut eth_address = [0u8; 20];
     eth_address.copy_from_slice(address);
+    Ok(eth_address)
+}
+
+/// Recovers and verifies eth address from signature.
+pub fn recover_and_verify_eth_address(
+    pda: &mut Account<Pda>,
+    message_hash: &[u8; 32],
+    recovery_id: u8,
+    signature: &[u8; 64],
+) -> Result<()> {
+    let eth_address = recover_eth_address(message_hash, recovery_id, signature)?;
+    msg!("Recovered address {:?}", eth_address);
 
     if eth_address != pda.tss_address {
         msg!("ECDSA signature error");
@@ -0,0 +1,66 @@
+//! Address derivation for off-chain clients and other programs.
+//!
+//! Resolving a foreign NFT to its Solana mint is a single lookup of the
+//! [`OriginRecord`](crate::state::OriginRecord) at
+//! [`find_origin_record_address`]; going the other way, the mint's
+//! [`NftRecord`](crate::state::NftRecord) at [`find_nft_record_address`]
+//! holds its origin chain, collection and token id.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::utils::COLLECTION_GROUP_SEED;
+pub use crate::utils::{find_origin_record_address, find_wrapped_mint_address};
+
+/// Finds the program state PDA, which is also the mint and update authority
+/// of every universal NFT.
+pub fn find_program_state_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"universal_nft"], &crate::ID)
+}
+
+/// Finds the collection account of the collection NFT `collection_mint`.
+pub fn find_collection_address(collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"collection", collection_mint.as_ref()], &crate::ID)
+}
+
+/// Finds the Token-2022 group mint of the collection NFT `collection_mint`,
+/// which the NFTs of a Token-2022 collection are members of.
+pub fn find_collection_group_address(collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COLLECTION_GROUP_SEED, collection_mint.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Finds the `NftRecord` of `mint`.
+pub fn find_nft_record_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"nft_record", mint.as_ref()], &crate::ID)
+}
+
+/// Finds the counter of NFTs of `collection_mint` minted by `wallet`.
+pub fn find_wallet_mint_counter_address(collection_mint: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"wallet_mints", collection_mint.as_ref(), wallet.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// Finds the receipt marking the inbound message with `message_hash` as processed.
+pub fn find_inbound_receipt_address(message_hash: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"inbound_receipt", message_hash.as_ref()], &crate::ID)
+}
+
+/// Finds the `OutboundTransfer` of the outbound message with `nonce`.
+pub fn find_outbound_transfer_address(nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"outbound", nonce.to_le_bytes().as_ref()], &crate::ID)
+}
+
+/// Returns the escrow token account holding `mint` while a native NFT is
+/// away on another chain.
+pub fn get_escrow_token_address(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(
+        &find_program_state_address().0,
+        mint,
+        token_program,
+    )
+}
@@ -14,4 +14,74 @@ pub enum ErrorCode {
     InvalidMessage,
     #[msg("Insufficient token balance")]
     InsufficientBalance,
+    #[msg("Collection does not match the cross-chain message")]
+    CollectionMismatch,
+    #[msg("Native NFTs cannot be minted into a wrapped collection")]
+    WrappedCollection,
+    #[msg("Non-transferable NFTs require the Token-2022 program")]
+    NonTransferableRequiresToken2022,
+    #[msg("Cross-chain message has already been processed")]
+    MessageAlreadyProcessed,
+    #[msg("TSS signature verification failed")]
+    TssAuthenticationFailed,
+    #[msg("NFT mint does not match the cross-chain message")]
+    InvalidMint,
+    #[msg("Origin record does not match the NFT's origin")]
+    InvalidOriginRecord,
+    #[msg("Unsupported cross-chain message version")]
+    UnsupportedMessageVersion,
+    #[msg("Unknown cross-chain message action")]
+    UnknownMessageAction,
+    #[msg("Cross-chain message exceeds the gateway payload limit, send metadata by reference")]
+    MessageTooLarge,
+    #[msg("Gas amount is outside the destination's configured bounds")]
+    GasAmountOutOfRange,
+    #[msg("Invalid gas configuration")]
+    InvalidGasConfig,
+    #[msg("Message does not match the outbound transfer")]
+    OutboundTransferMismatch,
+    #[msg("The outbound transfer has already completed")]
+    TransferAlreadyCompleted,
+    #[msg("The outbound transfer has not been pending long enough to be reclaimed")]
+    ReclaimTimeoutNotElapsed,
+    #[msg("Reclaiming requires the authority's signature or a TSS-signed proof")]
+    ReclaimProofRequired,
+    #[msg("Invalid reclaim timeout")]
+    InvalidReclaimTimeout,
+    #[msg("Batch accounts do not match the NFTs in the batch")]
+    InvalidBatch,
+    #[msg("Batched transfers cannot be sent to destinations taking ABI-encoded messages")]
+    BatchEncodingUnsupported,
+    #[msg("Minting has not started yet")]
+    MintNotStarted,
+    #[msg("Minting has ended")]
+    MintEnded,
+    #[msg("The collection has reached its maximum supply")]
+    MaxSupplyReached,
+    #[msg("The wallet has reached its mint limit for this collection")]
+    WalletMintLimitReached,
+    #[msg("Invalid mint limits")]
+    InvalidMintLimits,
+    #[msg("Only the collection authority may mint this collection")]
+    UnauthorizedMinter,
+    #[msg("Minter is not on the collection's allowlist")]
+    NotOnAllowlist,
+    #[msg("Token-2022 collections require their collection group account")]
+    CollectionGroupRequired,
+    #[msg("The NFT already has a token in circulation")]
+    NftAlreadyMinted,
+    #[msg("A returning native NFT must be locked in escrow")]
+    NftNotEscrowed,
+    #[msg("Non-transferable native NFTs cannot leave Solana")]
+    NativeNftNotTransferable,
+    #[msg("Inbound receipt does not match the message")]
+    InvalidInboundReceipt,
+    #[msg("Recipient does not match the message")]
+    RecipientMismatch,
+    #[msg("Gateway call sender is not trusted for the source chain")]
+    UntrustedSender,
+    #[msg("Collections with a per-wallet limit need the minter's wallet mint counter")]
+    WalletMintCounterRequired,
+    #[msg("TSS signature is malformed and recovers no signer")]
+    InvalidTssSignature,
 }
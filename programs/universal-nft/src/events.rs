@@ -1,5 +1,38 @@
+use crate::state::{MessageEncoding, MintLimits, MintPolicy};
 use anchor_lang::prelude::*;
 
+#[event]
+pub struct ProgramInitialized {
+    pub authority: Pubkey,
+    pub chain_id: u64,
+    pub tss_address: [u8; 20],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CollectionInitialized {
+    pub collection: Pubkey,
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub origin_chain: u64,
+    pub origin_collection: Pubkey,
+    pub token_program: Pubkey,
+    pub non_transferable: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftMinted {
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub token_id: u64,
+    pub original_chain: u64,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct CrossChainTransferEvent {
     pub mint: Pubkey,
@@ -8,4 +41,141 @@ pub struct CrossChainTransferEvent {
     pub recipient: [u8; 20],
     pub token_id: u64,
     pub timestamp: i64,
+    pub nonce: u64,
+    /// SHA-256 of the payload handed to the gateway, which keys the inbound
+    /// receipt on a Solana destination.
+    pub message_hash: [u8; 32],
+    pub original_chain: u64,
+    pub owner: Pubkey,
+    /// Whether the NFT was locked in escrow rather than burned.
+    pub escrowed: bool,
+}
+
+#[event]
+pub struct NftReceived {
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub token_id: u64,
+    pub original_chain: u64,
+    pub owner: Pubkey,
+    pub nonce: u64,
+    /// Key of the message's inbound receipt.
+    pub message_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WrappedMintCreated {
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub original_chain: u64,
+    pub origin_collection: Pubkey,
+    pub token_id: u64,
+    pub message_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NftReturnedToOrigin {
+    pub mint: Pubkey,
+    pub token_id: u64,
+    pub owner: Pubkey,
+    pub message_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CrossChainTransferReverted {
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub token_id: u64,
+    pub to_chain: u64,
+    pub user: Pubkey,
+    /// Lamports returned by the gateway with the revert.
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CrossChainTransferDelivered {
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub token_id: u64,
+    pub to_chain: u64,
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CrossChainTransferReclaimed {
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub token_id: u64,
+    pub to_chain: u64,
+    pub user: Pubkey,
+    /// Whether the authority signed in place of a TSS proof.
+    pub by_authority: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PausedUpdated {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TssAddressUpdated {
+    pub authority: Pubkey,
+    pub old_tss_address: [u8; 20],
+    pub tss_address: [u8; 20],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReclaimTimeoutUpdated {
+    pub authority: Pubkey,
+    pub reclaim_timeout: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintLimitsUpdated {
+    pub authority: Pubkey,
+    pub collection_mint: Pubkey,
+    pub mint_limits: MintLimits,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MintPolicyUpdated {
+    pub authority: Pubkey,
+    pub collection_mint: Pubkey,
+    pub mint_policy: MintPolicy,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DestinationConfigUpdated {
+    pub authority: Pubkey,
+    pub chain_id: u64,
+    pub encoding: MessageEncoding,
+    pub zrc20: [u8; 20],
+    pub gas_amount: u64,
+    pub min_gas_amount: u64,
+    pub max_gas_amount: u64,
+    pub trusted_sender: [u8; 20],
+    pub universal_contract: [u8; 20],
+    pub sender_alias: [u8; 20],
+    pub timestamp: i64,
+}
+
+/// Event of a message delivered to Solana, in the order `on_call` and
+/// `receive_nft_cross_chain` emit them once the message is processed.
+pub enum InboundEvent {
+    Delivered(CrossChainTransferDelivered),
+    ReturnedToOrigin(NftReturnedToOrigin),
+    WrappedMintCreated(WrappedMintCreated),
+    Received(NftReceived),
 }
@@ -1,6 +1,8 @@
-use crate::{error::ErrorCode, state::*};
+use crate::{error::ErrorCode, events::*, state::*};
 use anchor_lang::prelude::*;
+use std::mem::size_of;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SetPaused<'info> {
     pub authority: Signer<'info>,
@@ -13,8 +15,91 @@ pub struct SetPaused<'info> {
     pub pda: Account<'info, UniversalNftPda>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTssAddress<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetReclaimTimeout<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMintLimits<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMintPolicy<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(chain_id: u64)]
+pub struct SetDestinationConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + size_of::<DestinationConfig>(),
+        seeds = [b"destination", chain_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub destination_config: Account<'info, DestinationConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
 impl<'info> SetPaused<'info> {
-    pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+    pub fn set_paused(&mut self, paused: bool) -> Result<PausedUpdated> {
         require_keys_eq!(
             self.authority.key(),
             self.pda.authority,
@@ -22,7 +107,158 @@ impl<'info> SetPaused<'info> {
         );
 
         self.pda.paused = paused;
+        let event = PausedUpdated {
+            authority: self.authority.key(),
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
         msg!("Program paused status set to: {}", paused);
-        Ok(())
+        Ok(event)
+    }
+}
+
+impl<'info> SetTssAddress<'info> {
+    pub fn set_tss_address(&mut self, tss_address: [u8; 20]) -> Result<TssAddressUpdated> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.pda.authority,
+            ErrorCode::UnauthorizedAccess
+        );
+
+        let event = TssAddressUpdated {
+            authority: self.authority.key(),
+            old_tss_address: self.pda.tss_address,
+            tss_address,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        self.pda.tss_address = tss_address;
+        msg!("TSS address updated to: {:?}", tss_address);
+        Ok(event)
+    }
+}
+
+impl<'info> SetReclaimTimeout<'info> {
+    pub fn set_reclaim_timeout(&mut self, reclaim_timeout: i64) -> Result<ReclaimTimeoutUpdated> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.pda.authority,
+            ErrorCode::UnauthorizedAccess
+        );
+        require!(reclaim_timeout >= 0, ErrorCode::InvalidReclaimTimeout);
+
+        self.pda.reclaim_timeout = reclaim_timeout;
+        let event = ReclaimTimeoutUpdated {
+            authority: self.authority.key(),
+            reclaim_timeout,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        msg!("Reclaim timeout set to: {} seconds", reclaim_timeout);
+        Ok(event)
+    }
+}
+
+impl<'info> SetMintLimits<'info> {
+    pub fn set_mint_limits(&mut self, mint_limits: MintLimits) -> Result<MintLimitsUpdated> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.collection.authority,
+            ErrorCode::UnauthorizedAccess
+        );
+        mint_limits.validate(self.collection.total_supply)?;
+
+        self.collection.mint_limits = mint_limits;
+        let event = MintLimitsUpdated {
+            authority: self.authority.key(),
+            collection_mint: self.collection_mint.key(),
+            mint_limits,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        msg!(
+            "Mint limits of collection {} set to: {:?}",
+            self.collection_mint.key(),
+            mint_limits
+        );
+        Ok(event)
+    }
+}
+
+impl<'info> SetMintPolicy<'info> {
+    pub fn set_mint_policy(&mut self, mint_policy: MintPolicy) -> Result<MintPolicyUpdated> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.collection.authority,
+            ErrorCode::UnauthorizedAccess
+        );
+
+        self.collection.mint_policy = mint_policy;
+        let event = MintPolicyUpdated {
+            authority: self.authority.key(),
+            collection_mint: self.collection_mint.key(),
+            mint_policy,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        msg!(
+            "Mint policy of collection {} set to: {:?}",
+            self.collection_mint.key(),
+            mint_policy
+        );
+        Ok(event)
+    }
+}
+
+impl<'info> SetDestinationConfig<'info> {
+    pub fn set_destination_config(
+        &mut self,
+        chain_id: u64,
+        encoding: MessageEncoding,
+        zrc20: [u8; 20],
+        gas_amount: u64,
+        min_gas_amount: u64,
+        max_gas_amount: u64,
+        trusted_sender: [u8; 20],
+        universal_contract: [u8; 20],
+        sender_alias: [u8; 20],
+    ) -> Result<DestinationConfigUpdated> {
+        require_keys_eq!(
+            self.authority.key(),
+            self.pda.authority,
+            ErrorCode::UnauthorizedAccess
+        );
+        require!(
+            min_gas_amount <= gas_amount && gas_amount <= max_gas_amount,
+            ErrorCode::InvalidGasConfig
+        );
+
+        self.destination_config.set_inner(DestinationConfig {
+            chain_id,
+            encoding,
+            zrc20,
+            gas_amount,
+            min_gas_amount,
+            max_gas_amount,
+            trusted_sender,
+            universal_contract,
+            sender_alias,
+        });
+        let event = DestinationConfigUpdated {
+            authority: self.authority.key(),
+            chain_id,
+            encoding,
+            zrc20,
+            gas_amount,
+            min_gas_amount,
+            max_gas_amount,
+            trusted_sender,
+            universal_contract,
+            sender_alias,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        msg!(
+            "Destination chain {} configured with {:?} encoding and gas amount {}",
+            chain_id,
+            encoding,
+            gas_amount
+        );
+        Ok(event)
     }
 }
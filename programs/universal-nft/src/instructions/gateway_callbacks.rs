@@ -1,10 +1,23 @@
-use crate::{error::ErrorCode, state::*, GATEWAY_PROGRAM_ID};
-use anchor_lang::prelude::*;
+use crate::{
+    client::{find_inbound_receipt_address, find_nft_record_address},
+    error::ErrorCode,
+    events::*,
+    state::*,
+    utils::*,
+};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{mint_to, Mint, MintTo, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::Metadata,
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+/// Remaining accounts passed to `on_call` for each NFT of a batch after the
+/// first, whose accounts are the named ones, in order: the `NftRecord`, the
+/// mint, the `OriginRecord`, the recipient's ATA, the escrow ATA, the Metaplex
+/// metadata and the master edition.
+pub const RECEIVE_BATCH_ACCOUNTS_PER_NFT: usize = 7;
+
 #[derive(Accounts)]
 pub struct OnCall<'info> {
     #[account(
@@ -20,11 +33,9 @@ pub struct OnCall<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct OnCallComplete<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-
     #[account(
         mut,
         seeds = [b"universal_nft"],
@@ -32,43 +43,132 @@ pub struct OnCallComplete<'info> {
     )]
     pub pda: Account<'info, UniversalNftPda>,
 
+    /// PDA the gateway credits with the deposit, paying for every account the
+    /// message creates since the gateway passes no signer along
     #[account(
-        init_if_needed,
-        payer = payer,
-        space = 8 + size_of::<NftRecord>(),
-        seeds = [b"nft_record", nft_mint.key().as_ref()],
+        mut,
+        seeds = [b"connected"],
         bump
     )]
-    pub nft_record: Account<'info, NftRecord>,
+    pub connected: SystemAccount<'info>,
 
+    /// Gateway PDA, whose nonce tells apart messages from EVM chains
     #[account(
-        init_if_needed,
-        payer = payer,
-        mint::decimals = 0,
-        mint::authority = pda,
-        mint::freeze_authority = pda,
+        seeds = [b"meta"],
+        bump,
+        seeds::program = gateway::ID
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub gateway_pda: Account<'info, gateway::Pda>,
 
     #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = nft_mint,
-        associated_token::authority = recipient_authority,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: NFT record of the mint, created on first arrival
+    #[account(
+        mut,
+        seeds = [b"nft_record", nft_mint.key().as_ref()],
+        bump
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub nft_record: UncheckedAccount<'info>,
+
+    /// CHECK: Receipt of the message, created on delivery and derived from the message
+    #[account(mut)]
+    pub inbound_receipt: UncheckedAccount<'info>,
+
+    /// CHECK: Origin record of the NFT, derived from the decoded message and created on first arrival
+    #[account(mut)]
+    pub origin_record: UncheckedAccount<'info>,
+
+    /// CHECK: NFT mint, checked against the message; wrapped mints are PDAs created on first arrival
+    #[account(mut)]
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Recipient ATA, validated and created by the token metadata program
+    #[account(mut)]
+    pub recipient_token_account: UncheckedAccount<'info>,
 
-    /// CHECK: The recipient of the NFT
+    /// CHECK: The recipient of the NFT, checked against the message
     pub recipient_authority: UncheckedAccount<'info>,
 
-    /// CHECK: Gateway program for verification
-    pub gateway_program: UncheckedAccount<'info>,
+    /// CHECK: PDA-owned escrow ATA holding returning native NFTs
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&pda.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account of the collection NFT
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account of the collection NFT
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token-2022 group mint of the collection, for Token-2022 collections
+    #[account(
+        mut,
+        seeds = [COLLECTION_GROUP_SEED, collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection_group: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// Outbound transfer acknowledged by an `Ack` message, closed on delivery
+    #[account(mut)]
+    pub outbound_transfer: Option<Account<'info, OutboundTransfer>>,
+
+    /// CHECK: Sender of the acknowledged transfer, refunded the outbound transfer's rent
+    #[account(mut)]
+    pub user_authority: Option<UncheckedAccount<'info>>,
+
+    /// Config of the chain the message comes from, naming the sender trusted to deliver it
+    pub destination_config: Account<'info, DestinationConfig>,
+
+    /// CHECK: Instructions sysvar, used to verify the gateway caller and by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct OnRevert<'info> {
     #[account(
@@ -85,62 +185,288 @@ pub struct OnRevert<'info> {
     )]
     pub nft_record: Account<'info, NftRecord>,
 
+    /// Closed once the NFT is restored, returning its rent to the user
     #[account(
         mut,
-        seeds = [b"outbound", nft_mint.key().as_ref(), user_authority.key().as_ref()],
+        close = user_authority,
+        seeds = [b"outbound", outbound_transfer.message.nonce.to_le_bytes().as_ref()],
         bump
     )]
     pub outbound_transfer: Account<'info, OutboundTransfer>,
 
-    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = user_authority,
+        associated_token::token_program = token_program,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: The original owner
+    /// CHECK: The original owner, who sent the NFT and paid for the outbound transfer
+    #[account(
+        mut,
+        address = outbound_transfer.user
+    )]
     pub user_authority: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: PDA-owned escrow ATA holding the NFT if it was locked rather than burned
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&pda.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to verify the gateway caller and by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> OnCallComplete<'info> {
     pub fn on_call(
         &mut self,
-        gateway_program_key: Pubkey,
         pda_bump: u8,
+        connected_bump: u8,
         amount: u64,
         sender: [u8; 20],
         data: Vec<u8>,
-    ) -> Result<()> {
-        require!(
-            gateway_program_key == GATEWAY_PROGRAM_ID,
-            ErrorCode::InvalidCaller
-        );
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<InboundEvent>> {
+        verify_gateway_caller(&self.sysvar_instructions)?;
+
+        // Anyone can have the gateway call this program, so a message of any
+        // action or encoding only counts from the sender trusted for the
+        // chain it comes from.
+        if self.destination_config.trusted_sender != sender {
+            msg!(
+                "Message from chain {} sent by untrusted sender {:?}",
+                self.destination_config.chain_id,
+                sender
+            );
+            return err!(ErrorCode::UntrustedSender);
+        }
+
+        // Envelopes start with the magic, ABI messages with a padded wallet.
+        let cross_chain_msg = if data.starts_with(&MESSAGE_MAGIC) {
+            CrossChainMessage::decode(&data)?
+        } else {
+            self.decode_evm_message(&data)?
+        };
 
-        let cross_chain_msg: CrossChainMessage =
-            CrossChainMessage::try_from_slice(&data).map_err(|_| ErrorCode::InvalidMessage)?;
+        // Keyed by the re-encoded message rather than the raw payload, so a
+        // padded or differently encoded copy maps to the same receipt.
+        self.record_inbound_receipt(
+            connected_bump,
+            cross_chain_msg.message_hash(),
+            &cross_chain_msg,
+        )?;
 
         let token_id = cross_chain_msg.token_id;
 
+        let mut events = Vec::new();
         match cross_chain_msg.action {
-            CrossChainAction::Transfer => {
+            CrossChainAction::Transfer | CrossChainAction::TransferByReference => {
                 self.handle_incoming_nft_transfer_from_gateway(
                     pda_bump,
+                    connected_bump,
+                    &mut events,
                     cross_chain_msg,
                     sender,
                     amount,
                 )?;
+                msg!(
+                    "Cross-chain NFT transfer completed for token_id: {}",
+                    token_id
+                );
+            }
+            CrossChainAction::TransferBatch => {
+                let count = cross_chain_msg.tokens.len();
+                self.handle_incoming_batch_from_gateway(
+                    pda_bump,
+                    connected_bump,
+                    &mut events,
+                    cross_chain_msg,
+                    sender,
+                    amount,
+                    remaining_accounts,
+                )?;
+                msg!("Cross-chain batch of {} NFTs completed", count);
+            }
+            CrossChainAction::Ack => self.handle_transfer_ack(&mut events, cross_chain_msg)?,
+            CrossChainAction::Revert => {
+                msg!("Revert messages are only accepted by on_revert");
+                return err!(ErrorCode::InvalidMessage);
             }
         }
 
+        Ok(events)
+    }
+
+    /// Decodes a message from the EVM universal NFT contracts as an NFT of
+    /// `collection`, resolving the mint it maps to on Solana.
+    fn decode_evm_message(&self, data: &[u8]) -> Result<CrossChainMessage> {
+        // The gateway stores its incremented nonce once `execute` returns, so
+        // during the call it still holds the nonce of this message.
+        let mut cross_chain_msg = decode_abi_message(
+            data,
+            &AbiMessageContext {
+                nonce: self.gateway_pda.nonce,
+                origin: self.collection.origin,
+                destination_chain: self.pda.chain_id,
+            },
+        )?;
+
+        cross_chain_msg.mint = if cross_chain_msg.original_chain == self.pda.chain_id {
+            // Native NFTs were indexed by token id when they were minted.
+            let (origin_record, _) = find_origin_record_address(
+                cross_chain_msg.original_chain,
+                &cross_chain_msg.collection,
+                cross_chain_msg.token_id,
+            );
+            require_keys_eq!(
+                self.origin_record.key(),
+                origin_record,
+                ErrorCode::InvalidOriginRecord
+            );
+            require_keys_eq!(
+                *self.origin_record.owner,
+                crate::ID,
+                ErrorCode::InvalidOriginRecord
+            );
+            OriginRecord::try_deserialize(&mut &self.origin_record.data.borrow()[..])?.mint
+        } else {
+            find_wrapped_mint_address(
+                cross_chain_msg.original_chain,
+                &cross_chain_msg.collection,
+                cross_chain_msg.token_id,
+            )
+            .0
+        };
+
+        Ok(cross_chain_msg)
+    }
+
+    /// Creates the receipt marking `cross_chain_msg` as processed, rejecting a
+    /// message whose receipt already exists.
+    fn record_inbound_receipt(
+        &self,
+        connected_bump: u8,
+        message_hash: [u8; 32],
+        cross_chain_msg: &CrossChainMessage,
+    ) -> Result<()> {
+        let (address, bump) = find_inbound_receipt_address(&message_hash);
+        require_keys_eq!(
+            self.inbound_receipt.key(),
+            address,
+            ErrorCode::InvalidInboundReceipt
+        );
+        require!(
+            self.inbound_receipt.owner != &crate::ID,
+            ErrorCode::MessageAlreadyProcessed
+        );
+
+        let connected_seeds = &[b"connected".as_ref(), &[connected_bump]];
+        let receipt_seeds = &[b"inbound_receipt".as_ref(), message_hash.as_ref(), &[bump]];
+        create_program_account(
+            &self.inbound_receipt,
+            &self.connected,
+            &self.system_program,
+            8 + size_of::<InboundReceipt>(),
+            &[&connected_seeds[..], &receipt_seeds[..]],
+        )?;
+
+        InboundReceipt {
+            message_hash,
+            nonce: cross_chain_msg.nonce,
+            mint: self.nft_mint.key(),
+            token_id: cross_chain_msg.token_id,
+            timestamp: Clock::get()?.unix_timestamp,
+            processed: true,
+        }
+        .try_serialize(&mut &mut self.inbound_receipt.data.borrow_mut()[..])
+    }
+
+    fn inbound_receipt(&self) -> Result<InboundReceipt> {
+        InboundReceipt::try_deserialize(&mut &self.inbound_receipt.data.borrow()[..])
+    }
+
+    /// Marks the acknowledged outbound transfer delivered and closes it,
+    /// refunding its rent to the user who sent the NFT.
+    fn handle_transfer_ack(
+        &mut self,
+        events: &mut Vec<InboundEvent>,
+        ack: CrossChainMessage,
+    ) -> Result<()> {
+        let (Some(outbound_transfer), Some(user_authority)) = (
+            self.outbound_transfer.as_mut(),
+            self.user_authority.as_ref(),
+        ) else {
+            return err!(ErrorCode::OutboundTransferMismatch);
+        };
+
+        // Only the chain the transfer went to, whose config vouched for the
+        // sender, can acknowledge it.
+        let sent = &outbound_transfer.message;
+        require!(
+            sent.destination_chain == self.destination_config.chain_id
+                && sent.nonce == ack.nonce
+                && sent.mint == ack.mint
+                && sent.token_id == ack.token_id
+                && sent.original_chain == ack.original_chain
+                && sent.destination_chain == ack.destination_chain,
+            ErrorCode::OutboundTransferMismatch
+        );
+        require_keys_eq!(
+            user_authority.key(),
+            outbound_transfer.user,
+            ErrorCode::OutboundTransferMismatch
+        );
+
+        outbound_transfer.completed = true;
+
+        events.push(InboundEvent::Delivered(CrossChainTransferDelivered {
+            nonce: ack.nonce,
+            mint: ack.mint,
+            token_id: ack.token_id,
+            to_chain: ack.destination_chain,
+            user: outbound_transfer.user,
+            timestamp: Clock::get()?.unix_timestamp,
+        }));
+
+        outbound_transfer.close(user_authority.to_account_info())?;
+
         msg!(
-            "Cross-chain NFT transfer completed for token_id: {}",
-            token_id
+            "Cross-chain NFT transfer delivered: nonce={}, token_id={}",
+            ack.nonce,
+            ack.token_id
         );
         Ok(())
     }
@@ -148,83 +474,309 @@ impl<'info> OnCallComplete<'info> {
     fn handle_incoming_nft_transfer_from_gateway(
         &mut self,
         pda_bump: u8,
+        connected_bump: u8,
+        events: &mut Vec<InboundEvent>,
         cross_chain_msg: CrossChainMessage,
         sender: [u8; 20],
         amount: u64,
     ) -> Result<()> {
-        if cross_chain_msg.original_chain == self.pda.chain_id {
-            self.nft_record.locked = false;
-            msg!("NFT returned to original chain: {}", cross_chain_msg.mint);
-        } else {
-            self.nft_record.set_inner(NftRecord {
-                mint: self.nft_mint.key(),
-                original_chain: cross_chain_msg.original_chain,
-                token_id: cross_chain_msg.token_id,
-                locked: false,
-                name: format!("Wrapped {}", cross_chain_msg.name),
-                description: cross_chain_msg.description,
-                image: cross_chain_msg.image,
-            });
-            msg!("Wrapped NFT created: {}", self.nft_mint.key());
-        }
-
-        let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
-        let signer_seeds = &[&seeds[..]];
-
-        let mint_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            MintTo {
-                mint: self.nft_mint.to_account_info(),
-                to: self.recipient_token_account.to_account_info(),
-                authority: self.pda.to_account_info(),
-            },
-            signer_seeds,
+        require_keys_eq!(
+            self.recipient_authority.key(),
+            Pubkey::new_from_array(cross_chain_msg.recipient),
+            ErrorCode::RecipientMismatch
         );
-        mint_to(mint_ctx, 1)?;
+
+        let nft = InboundNftAccounts {
+            nft_mint: &self.nft_mint.to_account_info(),
+            origin_record: &self.origin_record.to_account_info(),
+            recipient_token_account: &self.recipient_token_account.to_account_info(),
+            escrow_token_account: &self.escrow_token_account.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            master_edition: &self.master_edition.to_account_info(),
+        };
+        self.receive_recorded_nft(
+            pda_bump,
+            connected_bump,
+            events,
+            &self.nft_record.to_account_info(),
+            &nft,
+            cross_chain_msg,
+        )?;
 
         msg!(
-            "NFT minted via gateway: {} with amount {} from sender {:?}",
-            self.nft_mint.key(),
+            "Gateway deposit of {} lamports from sender {:?}",
             amount,
             sender
         );
+        Ok(())
+    }
+
+    /// Mints every NFT of a `TransferBatch` message. The named accounts take
+    /// the first NFT and each other one brings its own accounts through
+    /// `remaining_accounts`.
+    fn handle_incoming_batch_from_gateway(
+        &mut self,
+        pda_bump: u8,
+        connected_bump: u8,
+        events: &mut Vec<InboundEvent>,
+        cross_chain_msg: CrossChainMessage,
+        sender: [u8; 20],
+        amount: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut token_ids: Vec<u64> = cross_chain_msg
+            .tokens
+            .iter()
+            .map(|token| token.token_id)
+            .collect();
+        token_ids.sort_unstable();
+        token_ids.dedup();
+        require!(
+            token_ids.len() == cross_chain_msg.tokens.len()
+                && remaining_accounts.len()
+                    == (token_ids.len() - 1) * RECEIVE_BATCH_ACCOUNTS_PER_NFT,
+            ErrorCode::InvalidBatch
+        );
+
+        let mut messages = cross_chain_msg.split_batch().into_iter();
+        if let Some(first) = messages.next() {
+            self.handle_incoming_nft_transfer_from_gateway(
+                pda_bump,
+                connected_bump,
+                events,
+                first,
+                sender,
+                amount,
+            )?;
+        }
+
+        for (token_msg, accounts) in
+            messages.zip(remaining_accounts.chunks_exact(RECEIVE_BATCH_ACCOUNTS_PER_NFT))
+        {
+            self.receive_batch_nft(pda_bump, connected_bump, events, token_msg, accounts)?;
+        }
 
         Ok(())
     }
+
+    fn receive_batch_nft(
+        &self,
+        pda_bump: u8,
+        connected_bump: u8,
+        events: &mut Vec<InboundEvent>,
+        cross_chain_msg: CrossChainMessage,
+        accounts: &'info [AccountInfo<'info>],
+    ) -> Result<()> {
+        let [nft_record, nft_mint, origin_record, recipient_token_account, escrow_token_account, metadata, master_edition] =
+            accounts
+        else {
+            return err!(ErrorCode::InvalidBatch);
+        };
+
+        require_keys_eq!(
+            nft_record.key(),
+            find_nft_record_address(&nft_mint.key()).0,
+            ErrorCode::InvalidBatch
+        );
+        require_keys_eq!(
+            escrow_token_account.key(),
+            get_associated_token_address_with_program_id(
+                &self.pda.key(),
+                &nft_mint.key(),
+                &self.token_program.key()
+            ),
+            ErrorCode::InvalidBatch
+        );
+        check_metadata_accounts(&nft_mint.key(), metadata, master_edition)?;
+
+        self.receive_recorded_nft(
+            pda_bump,
+            connected_bump,
+            events,
+            nft_record,
+            &InboundNftAccounts {
+                nft_mint,
+                origin_record,
+                recipient_token_account,
+                escrow_token_account,
+                metadata,
+                master_edition,
+            },
+            cross_chain_msg,
+        )
+    }
+
+    /// Receives one NFT, creating its `NftRecord` on first arrival.
+    fn receive_recorded_nft(
+        &self,
+        pda_bump: u8,
+        connected_bump: u8,
+        events: &mut Vec<InboundEvent>,
+        nft_record: &AccountInfo<'info>,
+        nft: &InboundNftAccounts<'_, 'info>,
+        cross_chain_msg: CrossChainMessage,
+    ) -> Result<()> {
+        let mut record = if nft_record.data_is_empty() {
+            let nft_mint_key = nft.nft_mint.key();
+            let (_, nft_record_bump) = find_nft_record_address(&nft_mint_key);
+            let connected_seeds = &[b"connected".as_ref(), &[connected_bump]];
+            let nft_record_seeds = &[
+                b"nft_record".as_ref(),
+                nft_mint_key.as_ref(),
+                &[nft_record_bump],
+            ];
+            create_program_account(
+                nft_record,
+                &self.connected,
+                &self.system_program,
+                8 + size_of::<NftRecord>(),
+                &[&connected_seeds[..], &nft_record_seeds[..]],
+            )?;
+            NftRecord {
+                mint: nft_mint_key,
+                collection_mint: self.collection_mint.key(),
+                original_chain: cross_chain_msg.original_chain,
+                token_id: cross_chain_msg.token_id,
+                locked: false,
+                escrowed: false,
+                name: String::new(),
+                description: String::new(),
+                image: String::new(),
+            }
+        } else {
+            NftRecord::try_deserialize(&mut &nft_record.data.borrow()[..])?
+        };
+
+        let connected_seeds = &[b"connected".as_ref(), &[connected_bump]];
+        receive_inbound_nft(
+            &InboundContext {
+                collection: &self.collection,
+                collection_nft: CollectionNftAccounts {
+                    mint: &self.collection_mint.to_account_info(),
+                    metadata: &self.collection_metadata.to_account_info(),
+                    master_edition: &self.collection_master_edition.to_account_info(),
+                    group: self.collection_group.as_deref(),
+                },
+                chain_id: self.pda.chain_id,
+                pda: &self.pda.to_account_info(),
+                pda_bump,
+                payer: &self.connected.to_account_info(),
+                payer_seeds: &[&connected_seeds[..]],
+                recipient_authority: &self.recipient_authority.to_account_info(),
+                token_metadata_program: &self.token_metadata_program.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+                token_program: &self.token_program.to_account_info(),
+                associated_token_program: &self.associated_token_program.to_account_info(),
+            },
+            nft,
+            &self.inbound_receipt()?,
+            &mut record,
+            cross_chain_msg,
+            events,
+        )?;
+
+        msg!("NFT minted via gateway: {}", nft.nft_mint.key());
+        record.try_serialize(&mut &mut nft_record.data.borrow_mut()[..])
+    }
 }
 
 impl<'info> OnRevert<'info> {
     pub fn on_revert(
         &mut self,
         pda_bump: u8,
-        _amount: u64,
-        _sender: Pubkey,
+        amount: u64,
+        sender: Pubkey,
         data: Vec<u8>,
-    ) -> Result<()> {
-        let cross_chain_msg: CrossChainMessage =
-            CrossChainMessage::try_from_slice(&data).map_err(|_| ErrorCode::InvalidMessage)?;
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<CrossChainTransferReverted>> {
+        verify_gateway_caller(&self.sysvar_instructions)?;
+
+        require!(
+            !self.outbound_transfer.completed,
+            ErrorCode::TransferAlreadyCompleted
+        );
+
+        // The NFT goes back to whoever sent it, so the revert message and the
+        // original depositor must both agree with the recorded transfer.
+        let revert_message = OutboundRevertMessage::decode(&data)?;
+        let sent = &self.outbound_transfer.message;
+        require!(
+            revert_message.outbound_transfer == self.outbound_transfer.key()
+                && revert_message.nonce == sent.nonce
+                && revert_message.mint == sent.mint
+                && revert_message.token_id == sent.token_id
+                && revert_message.user == self.outbound_transfer.user
+                && sender == self.outbound_transfer.user,
+            ErrorCode::OutboundTransferMismatch
+        );
+        require_keys_eq!(
+            self.nft_mint.key(),
+            sent.mint,
+            ErrorCode::OutboundTransferMismatch
+        );
 
-        self.nft_record.locked = false;
         self.outbound_transfer.completed = true;
 
         let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
         let signer_seeds = &[&seeds[..]];
 
-        let mint_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            MintTo {
-                mint: self.nft_mint.to_account_info(),
-                to: self.user_token_account.to_account_info(),
-                authority: self.pda.to_account_info(),
-            },
+        // The user's token accounts already exist, so the PDA never has to fund
+        // any account creation as the payer here.
+        let accounts = NftMetadataAccounts {
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            master_edition: &self.master_edition.to_account_info(),
+            mint: &self.nft_mint.to_account_info(),
+            authority: &self.pda.to_account_info(),
+            payer: &self.pda.to_account_info(),
+            token: &self.user_token_account.to_account_info(),
+            token_owner: &self.user_authority.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+        };
+
+        restore_locked_nft(
+            &accounts,
+            &self.escrow_token_account.to_account_info(),
+            &mut self.nft_record,
             signer_seeds,
-        );
-        mint_to(mint_ctx, 1)?;
+        )?;
+
+        // The named accounts hold the first NFT of a batch; the others come
+        // through the remaining accounts.
+        let sent = &self.outbound_transfer.message;
+        if sent.action == CrossChainAction::TransferBatch {
+            restore_batch_nfts(
+                &accounts,
+                &self.outbound_transfer.user,
+                &sent.tokens[1..],
+                remaining_accounts,
+                signer_seeds,
+            )?;
+        }
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        let events = sent
+            .split_batch()
+            .into_iter()
+            .map(|token| CrossChainTransferReverted {
+                nonce: revert_message.nonce,
+                mint: token.mint,
+                token_id: token.token_id,
+                to_chain: sent.destination_chain,
+                user: self.outbound_transfer.user,
+                amount,
+                timestamp,
+            })
+            .collect();
 
         msg!(
             "Cross-chain transfer reverted for token_id: {}",
-            cross_chain_msg.token_id
+            revert_message.token_id
         );
-        Ok(())
+        Ok(events)
     }
 }
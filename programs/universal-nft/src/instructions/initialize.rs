@@ -1,7 +1,8 @@
-use crate::state::*;
+use crate::{events::*, state::*};
 use anchor_lang::prelude::*;
 use std::mem::size_of;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(mut)]
@@ -20,15 +21,28 @@ pub struct Initialize<'info> {
 }
 
 impl<'info> Initialize<'info> {
-    pub fn initialize(&mut self, chain_id: u64) -> Result<()> {
+    pub fn initialize(
+        &mut self,
+        chain_id: u64,
+        tss_address: [u8; 20],
+    ) -> Result<ProgramInitialized> {
         self.pda.set_inner(UniversalNftPda {
             authority: self.authority.key(),
             chain_id,
             nonce: 0,
+            tss_address,
             paused: false,
+            reclaim_timeout: DEFAULT_RECLAIM_TIMEOUT,
         });
 
+        let event = ProgramInitialized {
+            authority: self.authority.key(),
+            chain_id,
+            tss_address,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
         msg!("Universal NFT program initialized for chain: {}", chain_id);
-        Ok(())
+        Ok(event)
     }
 }
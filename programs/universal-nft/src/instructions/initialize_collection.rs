@@ -1,40 +1,202 @@
-use crate::state::*;
-use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token::{Mint, Token},
+    token_interface::TokenInterface,
+};
 use std::mem::size_of;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeCollection<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    #[account(
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = pda,
+        mint::freeze_authority = pda,
+    )]
+    pub collection_mint: Account<'info, Mint>,
+
     #[account(
         init,
         payer = authority,
         space = 8 + size_of::<Collection>(),
-        seeds = [b"collection"],
+        seeds = [b"collection", collection_mint.key().as_ref()],
         bump
     )]
     pub collection: Account<'info, Collection>,
 
+    /// CHECK: PDA-owned ATA holding the collection NFT, created by the token metadata program
+    #[account(mut)]
+    pub collection_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token-2022 group mint of the collection, created here for Token-2022 collections
+    #[account(
+        mut,
+        seeds = [COLLECTION_GROUP_SEED, collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection_group: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Instructions sysvar required by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Program<'info, Token>,
+    /// Token program the collection's NFTs will be issued under
+    pub nft_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> InitializeCollection<'info> {
     pub fn initialize_collection(
         &mut self,
+        pda_bump: u8,
+        collection_group_bump: Option<u8>,
         name: String,
         symbol: String,
         base_uri: String,
-    ) -> Result<()> {
+        origin: Option<CollectionOrigin>,
+        non_transferable: bool,
+    ) -> Result<CollectionInitialized> {
+        require!(
+            !non_transferable || is_token_2022(&self.nft_token_program),
+            ErrorCode::NonTransferableRequiresToken2022
+        );
+
+        // Wrapped collections decide where inbound NFTs from another chain land,
+        // so only the program authority may register them.
+        if origin.is_some() {
+            require_keys_eq!(
+                self.authority.key(),
+                self.pda.authority,
+                ErrorCode::UnauthorizedAccess
+            );
+        }
+
         self.collection.set_inner(Collection {
             authority: self.authority.key(),
             name: name.clone(),
             symbol: symbol.clone(),
             base_uri,
             total_supply: 0,
+            collection_mint: self.collection_mint.key(),
+            origin: origin.unwrap_or(CollectionOrigin {
+                chain_id: self.pda.chain_id,
+                collection: self.collection_mint.key(),
+            }),
+            token_program: self.nft_token_program.key(),
+            non_transferable,
+            mint_limits: MintLimits::default(),
+            mint_policy: MintPolicy::default(),
         });
 
-        msg!("Collection initialized: {} ({})", name, symbol);
-        Ok(())
+        let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = NftMetadataAccounts {
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            metadata: &self.collection_metadata.to_account_info(),
+            master_edition: &self.collection_master_edition.to_account_info(),
+            mint: &self.collection_mint.to_account_info(),
+            authority: &self.pda.to_account_info(),
+            payer: &self.authority.to_account_info(),
+            token: &self.collection_token_account.to_account_info(),
+            token_owner: &self.pda.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+        };
+
+        create_nft_metadata(
+            &accounts,
+            name.clone(),
+            symbol.clone(),
+            self.collection.collection_uri(),
+            None,
+            signer_seeds,
+        )?;
+        mint_nft_token(&accounts, signer_seeds)?;
+
+        // Token-2022 NFTs cannot be verified against the Metaplex collection
+        // NFT, so they join a token group created alongside it instead.
+        match (
+            is_token_2022(&self.nft_token_program),
+            &self.collection_group,
+            collection_group_bump,
+        ) {
+            (true, Some(collection_group), Some(collection_group_bump)) => {
+                let collection_mint = self.collection_mint.key();
+                let group_seeds = &[
+                    COLLECTION_GROUP_SEED,
+                    collection_mint.as_ref(),
+                    &[collection_group_bump],
+                ];
+                create_token_2022_collection_group(
+                    collection_group,
+                    &self.pda.to_account_info(),
+                    &self.authority.to_account_info(),
+                    &self.system_program.to_account_info(),
+                    &self.nft_token_program.to_account_info(),
+                    &[&seeds[..], &group_seeds[..]],
+                )?;
+            }
+            (false, None, _) => {}
+            _ => return err!(ErrorCode::CollectionGroupRequired),
+        }
+
+        let event = CollectionInitialized {
+            collection: self.collection.key(),
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            name: name.clone(),
+            symbol: symbol.clone(),
+            origin_chain: self.collection.origin.chain_id,
+            origin_collection: self.collection.origin.collection,
+            token_program: self.collection.token_program,
+            non_transferable,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        msg!(
+            "Collection initialized: {} ({}) with collection NFT {}",
+            name,
+            symbol,
+            self.collection_mint.key()
+        );
+        Ok(event)
     }
 }
@@ -0,0 +1,251 @@
+use crate::{client::find_nft_record_address, error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::AssociatedToken, metadata::Metadata, token_interface::TokenInterface,
+};
+
+/// Remaining accounts passed for each NFT of a batch, in order: the mint
+/// (fresh keypair, signer), its `NftRecord`, its `OriginRecord`, the
+/// recipient's ATA, the Metaplex metadata and the master edition.
+pub const MINT_BATCH_ACCOUNTS_PER_NFT: usize = 6;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MintBatchItem {
+    pub name: String,
+    pub description: String,
+    pub image: String,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+
+    #[account(
+        mut,
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: The recipient of every NFT in the batch
+    pub recipient_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Number of NFTs of the collection minted by `payer`, required and
+    /// created on the first mint when the collection has a per-wallet limit
+    #[account(
+        mut,
+        seeds = [b"wallet_mints", collection_mint.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub wallet_mint_counter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account of the collection NFT
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account of the collection NFT
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token-2022 group mint of the collection, for Token-2022 collections
+    #[account(
+        mut,
+        seeds = [COLLECTION_GROUP_SEED, collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection_group: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Instructions sysvar required by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MintBatch<'info> {
+    pub fn mint_batch(
+        &mut self,
+        pda_bump: u8,
+        nfts: Vec<MintBatchItem>,
+        proof: Vec<[u8; 32]>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<NftMinted>> {
+        require!(!self.pda.paused, ErrorCode::ProgramPaused);
+        require_eq!(
+            self.collection.origin.chain_id,
+            self.pda.chain_id,
+            ErrorCode::WrappedCollection
+        );
+        self.collection.check_minter(&self.payer.key(), &proof)?;
+        require!(
+            !nfts.is_empty()
+                && remaining_accounts.len() == nfts.len() * MINT_BATCH_ACCOUNTS_PER_NFT,
+            ErrorCode::InvalidBatch
+        );
+
+        let count = nfts.len() as u64;
+        self.collection
+            .check_mint_allowed(Clock::get()?.unix_timestamp, count)?;
+        // Counted against the signer, the identity the mint policy checked.
+        record_wallet_mints(
+            self.wallet_mint_counter.as_deref(),
+            &self.payer,
+            &self.system_program,
+            &self.collection_mint.key(),
+            count,
+            self.collection.mint_limits.max_per_wallet,
+        )?;
+
+        let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let first_token_id = self.collection.total_supply;
+
+        let mut events = Vec::with_capacity(nfts.len());
+        for (nft, accounts) in nfts
+            .into_iter()
+            .zip(remaining_accounts.chunks_exact(MINT_BATCH_ACCOUNTS_PER_NFT))
+        {
+            events.push(self.mint_one(nft, accounts, signer_seeds)?);
+        }
+
+        msg!(
+            "Batch minted {} NFTs (token_ids {}..{})",
+            count,
+            first_token_id,
+            self.collection.total_supply
+        );
+        Ok(events)
+    }
+
+    fn mint_one(
+        &mut self,
+        nft: MintBatchItem,
+        accounts: &'info [AccountInfo<'info>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<NftMinted> {
+        let [nft_mint, nft_record, origin_record, recipient_token_account, metadata, master_edition] =
+            accounts
+        else {
+            return err!(ErrorCode::InvalidBatch);
+        };
+
+        require!(nft_mint.is_signer, ErrorCode::InvalidMint);
+        check_metadata_accounts(&nft_mint.key(), metadata, master_edition)?;
+
+        let (nft_record_address, nft_record_bump) = find_nft_record_address(&nft_mint.key());
+        require_keys_eq!(
+            nft_record.key(),
+            nft_record_address,
+            ErrorCode::InvalidBatch
+        );
+
+        let record = NftRecord {
+            mint: nft_mint.key(),
+            collection_mint: self.collection_mint.key(),
+            original_chain: self.pda.chain_id,
+            token_id: self.collection.total_supply,
+            locked: false,
+            escrowed: false,
+            name: nft.name,
+            description: nft.description,
+            image: nft.image,
+        };
+
+        let nft_mint_key = nft_mint.key();
+        let nft_record_seeds = &[
+            b"nft_record".as_ref(),
+            nft_mint_key.as_ref(),
+            &[nft_record_bump],
+        ];
+        create_program_account(
+            nft_record,
+            &self.payer,
+            &self.system_program,
+            8 + size_of::<NftRecord>(),
+            &[&nft_record_seeds[..]],
+        )?;
+        record.try_serialize(&mut &mut nft_record.data.borrow_mut()[..])?;
+
+        record_origin(
+            origin_record,
+            &self.payer,
+            &self.system_program,
+            &[],
+            &OriginRecord {
+                original_chain: self.pda.chain_id,
+                origin_collection: self.collection_mint.key(),
+                token_id: record.token_id,
+                mint: nft_mint.key(),
+                collection_mint: self.collection_mint.key(),
+            },
+        )?;
+
+        self.collection.total_supply += 1;
+
+        let uri = self
+            .collection
+            .token_uri(record.original_chain, record.token_id);
+
+        mint_universal_nft(
+            &NftMetadataAccounts {
+                token_metadata_program: &self.token_metadata_program.to_account_info(),
+                metadata,
+                master_edition,
+                mint: nft_mint,
+                authority: &self.pda.to_account_info(),
+                payer: &self.payer.to_account_info(),
+                token: recipient_token_account,
+                token_owner: &self.recipient_authority.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+                token_program: &self.token_program.to_account_info(),
+                associated_token_program: &self.associated_token_program.to_account_info(),
+            },
+            &CollectionNftAccounts {
+                mint: &self.collection_mint.to_account_info(),
+                metadata: &self.collection_metadata.to_account_info(),
+                master_edition: &self.collection_master_edition.to_account_info(),
+                group: self.collection_group.as_deref(),
+            },
+            record.name,
+            self.collection.symbol.clone(),
+            uri,
+            self.collection.non_transferable,
+            signer_seeds,
+        )?;
+
+        Ok(NftMinted {
+            mint: nft_mint.key(),
+            collection_mint: self.collection_mint.key(),
+            token_id: record.token_id,
+            original_chain: record.original_chain,
+            owner: self.recipient_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        })
+    }
+}
@@ -1,10 +1,10 @@
-use crate::{error::ErrorCode, state::*};
-use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{mint_to, Mint, MintTo, Token, TokenAccount},
+    associated_token::AssociatedToken, metadata::Metadata, token_interface::TokenInterface,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct MintNft<'info> {
     #[account(mut)]
@@ -19,7 +19,7 @@ pub struct MintNft<'info> {
 
     #[account(
         mut,
-        seeds = [b"collection"],
+        seeds = [b"collection", collection_mint.key().as_ref()],
         bump
     )]
     pub collection: Account<'info, Collection>,
@@ -36,24 +36,90 @@ pub struct MintNft<'info> {
     #[account(
         init,
         payer = payer,
-        mint::decimals = 0,
-        mint::authority = pda,
-        mint::freeze_authority = pda,
+        space = 8 + size_of::<OriginRecord>(),
+        seeds = [
+            b"origin",
+            pda.chain_id.to_le_bytes().as_ref(),
+            collection_mint.key().as_ref(),
+            collection.total_supply.to_le_bytes().as_ref()
+        ],
+        bump
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub origin_record: Account<'info, OriginRecord>,
 
-    #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = nft_mint,
-        associated_token::authority = recipient_authority,
-    )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    /// Fresh keypair for the NFT mint, created with the collection's token program
+    #[account(mut)]
+    pub nft_mint: Signer<'info>,
+
+    /// CHECK: Recipient ATA, validated and created during minting
+    #[account(mut)]
+    pub recipient_token_account: UncheckedAccount<'info>,
 
     /// CHECK: The recipient of the NFT
     pub recipient_authority: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Number of NFTs of the collection minted by `payer`, required and
+    /// created on the first mint when the collection has a per-wallet limit
+    #[account(
+        mut,
+        seeds = [b"wallet_mints", collection_mint.key().as_ref(), payer.key().as_ref()],
+        bump
+    )]
+    pub wallet_mint_counter: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account of the collection NFT
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account of the collection NFT
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token-2022 group mint of the collection, for Token-2022 collections
+    #[account(
+        mut,
+        seeds = [COLLECTION_GROUP_SEED, collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection_group: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -65,40 +131,99 @@ impl<'info> MintNft<'info> {
         name: String,
         description: String,
         image: String,
-    ) -> Result<()> {
+        proof: Vec<[u8; 32]>,
+    ) -> Result<NftMinted> {
         require!(!self.pda.paused, ErrorCode::ProgramPaused);
+        require_eq!(
+            self.collection.origin.chain_id,
+            self.pda.chain_id,
+            ErrorCode::WrappedCollection
+        );
+        self.collection.check_minter(&self.payer.key(), &proof)?;
+
+        self.collection
+            .check_mint_allowed(Clock::get()?.unix_timestamp, 1)?;
+        // Counted against the signer, the identity the mint policy checked.
+        record_wallet_mints(
+            self.wallet_mint_counter.as_deref(),
+            &self.payer,
+            &self.system_program,
+            &self.collection_mint.key(),
+            1,
+            self.collection.mint_limits.max_per_wallet,
+        )?;
 
         self.nft_record.set_inner(NftRecord {
             mint: self.nft_mint.key(),
+            collection_mint: self.collection_mint.key(),
             original_chain: self.pda.chain_id,
             token_id: self.collection.total_supply,
             locked: false,
+            escrowed: false,
             name: name.clone(),
             description,
             image,
         });
 
+        self.origin_record.set_inner(OriginRecord {
+            original_chain: self.pda.chain_id,
+            origin_collection: self.collection_mint.key(),
+            token_id: self.nft_record.token_id,
+            mint: self.nft_mint.key(),
+            collection_mint: self.collection_mint.key(),
+        });
+
         self.collection.total_supply += 1;
 
         let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
         let signer_seeds = &[&seeds[..]];
 
-        let mint_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            MintTo {
-                mint: self.nft_mint.to_account_info(),
-                to: self.recipient_token_account.to_account_info(),
-                authority: self.pda.to_account_info(),
+        let uri = self
+            .collection
+            .token_uri(self.nft_record.original_chain, self.nft_record.token_id);
+
+        mint_universal_nft(
+            &NftMetadataAccounts {
+                token_metadata_program: &self.token_metadata_program.to_account_info(),
+                metadata: &self.metadata.to_account_info(),
+                master_edition: &self.master_edition.to_account_info(),
+                mint: &self.nft_mint.to_account_info(),
+                authority: &self.pda.to_account_info(),
+                payer: &self.payer.to_account_info(),
+                token: &self.recipient_token_account.to_account_info(),
+                token_owner: &self.recipient_authority.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+                token_program: &self.token_program.to_account_info(),
+                associated_token_program: &self.associated_token_program.to_account_info(),
+            },
+            &CollectionNftAccounts {
+                mint: &self.collection_mint.to_account_info(),
+                metadata: &self.collection_metadata.to_account_info(),
+                master_edition: &self.collection_master_edition.to_account_info(),
+                group: self.collection_group.as_deref(),
             },
+            name,
+            self.collection.symbol.clone(),
+            uri,
+            self.collection.non_transferable,
             signer_seeds,
-        );
-        mint_to(mint_ctx, 1)?;
+        )?;
+
+        let event = NftMinted {
+            mint: self.nft_mint.key(),
+            collection_mint: self.collection_mint.key(),
+            token_id: self.nft_record.token_id,
+            original_chain: self.nft_record.original_chain,
+            owner: self.recipient_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
         msg!(
             "NFT minted: {} (token_id: {})",
             self.nft_mint.key(),
             self.nft_record.token_id
         );
-        Ok(())
+        Ok(event)
     }
 }
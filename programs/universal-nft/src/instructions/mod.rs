@@ -5,6 +5,9 @@ pub mod send_nft_cross_chain;
 pub mod receive_nft_cross_chain;
 pub mod gateway_callbacks;
 pub mod admin;
+pub mod reclaim_stuck_transfer;
+pub mod mint_batch;
+pub mod send_batch_cross_chain;
 
 pub use initialize::*;
 pub use initialize_collection::*;
@@ -13,3 +16,6 @@ pub use send_nft_cross_chain::*;
 pub use receive_nft_cross_chain::*;
 pub use gateway_callbacks::*;
 pub use admin::*;
+pub use reclaim_stuck_transfer::*;
+pub use mint_batch::*;
+pub use send_batch_cross_chain::*;
@@ -1,11 +1,14 @@
-use crate::{error::ErrorCode, state::*};
-use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{mint_to, Mint, MintTo, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::Metadata,
+    token_interface::TokenInterface,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(cross_chain_msg: CrossChainMessage)]
 pub struct ReceiveNftCrossChain<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -17,6 +20,12 @@ pub struct ReceiveNftCrossChain<'info> {
     )]
     pub pda: Account<'info, UniversalNftPda>,
 
+    #[account(
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
     #[account(
         init_if_needed,
         payer = payer,
@@ -29,24 +38,97 @@ pub struct ReceiveNftCrossChain<'info> {
     #[account(
         init_if_needed,
         payer = payer,
-        mint::decimals = 0,
-        mint::authority = pda,
-        mint::freeze_authority = pda,
+        space = 8 + size_of::<InboundReceipt>(),
+        seeds = [b"inbound_receipt", cross_chain_msg.message_hash().as_ref()],
+        bump
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub inbound_receipt: Account<'info, InboundReceipt>,
 
+    /// CHECK: Origin record of the NFT, created on first arrival
     #[account(
-        init_if_needed,
-        payer = payer,
-        associated_token::mint = nft_mint,
-        associated_token::authority = recipient_authority,
+        mut,
+        seeds = [
+            b"origin",
+            cross_chain_msg.original_chain.to_le_bytes().as_ref(),
+            cross_chain_msg.collection.as_ref(),
+            cross_chain_msg.token_id.to_le_bytes().as_ref()
+        ],
+        bump
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub origin_record: UncheckedAccount<'info>,
+
+    /// CHECK: NFT mint, checked against the message; wrapped mints are PDAs created on first arrival
+    #[account(mut)]
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Recipient ATA, validated and created by the token metadata program
+    #[account(mut)]
+    pub recipient_token_account: UncheckedAccount<'info>,
 
-    /// CHECK: The recipient of the NFT
+    /// CHECK: The recipient of the NFT, as named in the signed message
+    #[account(address = Pubkey::new_from_array(cross_chain_msg.recipient) @ ErrorCode::RecipientMismatch)]
     pub recipient_authority: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: PDA-owned escrow ATA holding returning native NFTs
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&pda.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account of the collection NFT
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account of the collection NFT
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token-2022 group mint of the collection, for Token-2022 collections
+    #[account(
+        mut,
+        seeds = [COLLECTION_GROUP_SEED, collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection_group: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -56,53 +138,94 @@ impl<'info> ReceiveNftCrossChain<'info> {
         &mut self,
         pda_bump: u8,
         cross_chain_msg: CrossChainMessage,
-    ) -> Result<()> {
+        signature: [u8; 64],
+        recovery_id: u8,
+    ) -> Result<Vec<InboundEvent>> {
         require!(!self.pda.paused, ErrorCode::ProgramPaused);
 
+        // Anyone may relay the message, but only a TSS-signed one is accepted.
+        let message_hash = inbound_message_hash(self.pda.chain_id, &cross_chain_msg)?;
+        verify_tss_signature(
+            &self.pda.tss_address,
+            &message_hash,
+            recovery_id,
+            &signature,
+        )?;
+        require!(
+            !self.inbound_receipt.processed,
+            ErrorCode::MessageAlreadyProcessed
+        );
+
+        self.inbound_receipt.set_inner(InboundReceipt {
+            message_hash: cross_chain_msg.message_hash(),
+            nonce: cross_chain_msg.nonce,
+            mint: self.nft_mint.key(),
+            token_id: cross_chain_msg.token_id,
+            timestamp: Clock::get()?.unix_timestamp,
+            processed: true,
+        });
+
+        let mut events = Vec::new();
         match cross_chain_msg.action {
-            CrossChainAction::Transfer => {
-                self.handle_incoming_nft_transfer(pda_bump, cross_chain_msg)?;
+            CrossChainAction::Transfer | CrossChainAction::TransferByReference => {
+                self.handle_incoming_nft_transfer(pda_bump, &mut events, cross_chain_msg)?;
+            }
+            CrossChainAction::Ack => {
+                msg!("Acknowledgements are only accepted through the gateway");
+                return err!(ErrorCode::InvalidMessage);
+            }
+            CrossChainAction::TransferBatch => {
+                msg!("Batched transfers are only accepted through the gateway");
+                return err!(ErrorCode::InvalidMessage);
+            }
+            CrossChainAction::Revert => {
+                msg!("Revert messages are only accepted by on_revert");
+                return err!(ErrorCode::InvalidMessage);
             }
         }
 
-        Ok(())
+        Ok(events)
     }
 
     fn handle_incoming_nft_transfer(
         &mut self,
         pda_bump: u8,
+        events: &mut Vec<InboundEvent>,
         cross_chain_msg: CrossChainMessage,
     ) -> Result<()> {
-        if cross_chain_msg.original_chain == self.pda.chain_id {
-            self.nft_record.locked = false;
-            msg!("NFT returned to original chain: {}", cross_chain_msg.mint);
-        } else {
-            self.nft_record.set_inner(NftRecord {
-                mint: self.nft_mint.key(),
-                original_chain: cross_chain_msg.original_chain,
-                token_id: cross_chain_msg.token_id,
-                locked: false,
-                name: format!("Wrapped {}", cross_chain_msg.name),
-                description: cross_chain_msg.description,
-                image: cross_chain_msg.image,
-            });
-            msg!("Wrapped NFT created: {}", self.nft_mint.key());
-        }
-
-        let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
-        let signer_seeds = &[&seeds[..]];
-
-        let mint_ctx = CpiContext::new_with_signer(
-            self.token_program.to_account_info(),
-            MintTo {
-                mint: self.nft_mint.to_account_info(),
-                to: self.recipient_token_account.to_account_info(),
-                authority: self.pda.to_account_info(),
+        receive_inbound_nft(
+            &InboundContext {
+                collection: &self.collection,
+                collection_nft: CollectionNftAccounts {
+                    mint: &self.collection_mint.to_account_info(),
+                    metadata: &self.collection_metadata.to_account_info(),
+                    master_edition: &self.collection_master_edition.to_account_info(),
+                    group: self.collection_group.as_deref(),
+                },
+                chain_id: self.pda.chain_id,
+                pda: &self.pda.to_account_info(),
+                pda_bump,
+                payer: &self.payer.to_account_info(),
+                payer_seeds: &[],
+                recipient_authority: &self.recipient_authority.to_account_info(),
+                token_metadata_program: &self.token_metadata_program.to_account_info(),
+                system_program: &self.system_program.to_account_info(),
+                sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+                token_program: &self.token_program.to_account_info(),
+                associated_token_program: &self.associated_token_program.to_account_info(),
             },
-            signer_seeds,
-        );
-        mint_to(mint_ctx, 1)?;
-
-        Ok(())
+            &InboundNftAccounts {
+                nft_mint: &self.nft_mint.to_account_info(),
+                origin_record: &self.origin_record.to_account_info(),
+                recipient_token_account: &self.recipient_token_account.to_account_info(),
+                escrow_token_account: &self.escrow_token_account.to_account_info(),
+                metadata: &self.metadata.to_account_info(),
+                master_edition: &self.master_edition.to_account_info(),
+            },
+            &self.inbound_receipt,
+            &mut self.nft_record,
+            cross_chain_msg,
+            events,
+        )
     }
 }
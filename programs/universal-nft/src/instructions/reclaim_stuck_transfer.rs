@@ -0,0 +1,182 @@
+use crate::{error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::sysvar};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    metadata::Metadata,
+    token_interface::{Mint, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimStuckTransfer<'info> {
+    /// The user who sent the NFT, who gets it back along with the outbound transfer's rent
+    #[account(
+        mut,
+        address = outbound_transfer.user
+    )]
+    pub user: Signer<'info>,
+
+    /// Program authority, whose signature stands in for a TSS-signed proof
+    #[account(address = pda.authority)]
+    pub authority: Option<Signer<'info>>,
+
+    #[account(
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"outbound", outbound_transfer.message.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_transfer: Account<'info, OutboundTransfer>,
+
+    #[account(
+        mut,
+        seeds = [b"nft_record", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_record: Account<'info, NftRecord>,
+
+    #[account(
+        mut,
+        address = outbound_transfer.message.mint,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: The user's ATA, created if it was closed after the NFT left
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&user.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub user_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: PDA-owned escrow ATA holding the NFT if it was locked rather than burned
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&pda.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex master edition account, initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by the token metadata program
+    #[account(address = sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ReclaimStuckTransfer<'info> {
+    pub fn reclaim_stuck_transfer(
+        &mut self,
+        pda_bump: u8,
+        signature: Option<[u8; 64]>,
+        recovery_id: u8,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<CrossChainTransferReclaimed>> {
+        // A pause holds reclaims too: while the program is stopped over a
+        // suspected key compromise, a forged proof must not free an NFT that
+        // was already minted on the destination chain.
+        require!(!self.pda.paused, ErrorCode::ProgramPaused);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(self.outbound_transfer.timestamp) >= self.pda.reclaim_timeout,
+            ErrorCode::ReclaimTimeoutNotElapsed
+        );
+
+        // Without the authority's signature, the TSS must attest that the
+        // message was never executed, so the NFT cannot exist on both chains.
+        if self.authority.is_none() {
+            let signature = signature.ok_or(ErrorCode::ReclaimProofRequired)?;
+            let proof_hash =
+                reclaim_proof_hash(self.pda.chain_id, &self.outbound_transfer.message)?;
+            verify_tss_signature(&self.pda.tss_address, &proof_hash, recovery_id, &signature)?;
+        }
+
+        self.outbound_transfer.completed = true;
+
+        let seeds = &[b"universal_nft".as_ref(), &[pda_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let accounts = NftMetadataAccounts {
+            token_metadata_program: &self.token_metadata_program.to_account_info(),
+            metadata: &self.metadata.to_account_info(),
+            master_edition: &self.master_edition.to_account_info(),
+            mint: &self.nft_mint.to_account_info(),
+            authority: &self.pda.to_account_info(),
+            payer: &self.user.to_account_info(),
+            token: &self.user_token_account.to_account_info(),
+            token_owner: &self.user.to_account_info(),
+            system_program: &self.system_program.to_account_info(),
+            sysvar_instructions: &self.sysvar_instructions.to_account_info(),
+            token_program: &self.token_program.to_account_info(),
+            associated_token_program: &self.associated_token_program.to_account_info(),
+        };
+
+        restore_locked_nft(
+            &accounts,
+            &self.escrow_token_account.to_account_info(),
+            &mut self.nft_record,
+            signer_seeds,
+        )?;
+
+        // The named accounts hold the first NFT of a batch; the others come
+        // through the remaining accounts.
+        let sent = &self.outbound_transfer.message;
+        if sent.action == CrossChainAction::TransferBatch {
+            restore_batch_nfts(
+                &accounts,
+                &self.user.key(),
+                &sent.tokens[1..],
+                remaining_accounts,
+                signer_seeds,
+            )?;
+        }
+
+        let events = sent
+            .split_batch()
+            .into_iter()
+            .map(|token| CrossChainTransferReclaimed {
+                nonce: sent.nonce,
+                mint: token.mint,
+                token_id: token.token_id,
+                to_chain: sent.destination_chain,
+                user: self.user.key(),
+                by_authority: self.authority.is_some(),
+                timestamp: now,
+            })
+            .collect();
+
+        msg!(
+            "Stuck cross-chain transfer reclaimed: nonce={}, token_id={}",
+            self.outbound_transfer.message.nonce,
+            self.outbound_transfer.message.token_id
+        );
+        Ok(events)
+    }
+}
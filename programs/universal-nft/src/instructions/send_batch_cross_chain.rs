@@ -0,0 +1,316 @@
+use crate::{client::find_nft_record_address, error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+use anchor_spl::{
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+/// Remaining accounts passed for each NFT of a batch, in order: the mint, its
+/// `NftRecord`, the user's ATA and the PDA-owned escrow ATA.
+pub const SEND_BATCH_ACCOUNTS_PER_NFT: usize = 4;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(destination_chain: u64)]
+pub struct SendBatchCrossChain<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"universal_nft"],
+        bump
+    )]
+    pub pda: Account<'info, UniversalNftPda>,
+
+    #[account(
+        seeds = [b"collection", collection_mint.key().as_ref()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+
+    /// CHECK: Collection NFT mint, used as the seed of `collection`
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Outbound transfer record, created here once the size of the batch is known
+    #[account(
+        mut,
+        seeds = [b"outbound", pda.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_transfer: UncheckedAccount<'info>,
+
+    /// Config of the destination chain, which must exist to send there
+    #[account(
+        seeds = [b"destination", destination_chain.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub destination_config: Account<'info, DestinationConfig>,
+
+    /// CHECK: ZetaChain Gateway PDA
+    #[account(mut)]
+    pub gateway_pda: UncheckedAccount<'info>,
+
+    /// CHECK: ZetaChain Gateway Program
+    pub gateway_program: UncheckedAccount<'info>,
+
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SendBatchCrossChain<'info> {
+    pub fn send_batch_cross_chain(
+        &mut self,
+        outbound_transfer_bump: u8,
+        destination_chain: u64,
+        recipient: [u8; 20],
+        gas_amount: Option<u64>,
+        abort_address: Option<[u8; 20]>,
+        remaining_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<CrossChainTransferEvent>> {
+        require!(!self.pda.paused, ErrorCode::ProgramPaused);
+        let gas_amount = self.destination_config.resolve_gas_amount(gas_amount)?;
+        require!(
+            self.destination_config.encoding != MessageEncoding::Abi,
+            ErrorCode::BatchEncodingUnsupported
+        );
+        require!(
+            !remaining_accounts.is_empty()
+                && remaining_accounts
+                    .len()
+                    .is_multiple_of(SEND_BATCH_ACCOUNTS_PER_NFT),
+            ErrorCode::InvalidBatch
+        );
+
+        let mut tokens = Vec::with_capacity(remaining_accounts.len() / SEND_BATCH_ACCOUNTS_PER_NFT);
+        let mut escrowed = Vec::with_capacity(tokens.capacity());
+        for accounts in remaining_accounts.chunks_exact(SEND_BATCH_ACCOUNTS_PER_NFT) {
+            let (token, nft_escrowed) = self.take_nft(accounts)?;
+            tokens.push(token);
+            escrowed.push(nft_escrowed);
+        }
+
+        let nonce = self.pda.nonce;
+        self.pda.nonce += 1;
+
+        // Every NFT of the batch travels by reference, and the first one also
+        // fills the single-token fields that acks and reverts are checked on.
+        let cross_chain_msg = CrossChainMessage {
+            action: CrossChainAction::TransferBatch,
+            nonce,
+            token_id: tokens[0].token_id,
+            mint: tokens[0].mint,
+            collection: self.collection.origin.collection,
+            original_chain: self.collection.origin.chain_id,
+            destination_chain,
+            recipient: evm_recipient(&recipient),
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            uri: tokens[0].uri.clone(),
+            tokens,
+        };
+        let message_data = cross_chain_msg.encode();
+
+        let revert_options = gateway::RevertOptions {
+            revert_address: crate::ID,
+            abort_address: abort_address.unwrap_or_default(),
+            call_on_revert: true,
+            revert_message: OutboundRevertMessage {
+                outbound_transfer: self.outbound_transfer.key(),
+                nonce,
+                mint: cross_chain_msg.mint,
+                token_id: cross_chain_msg.token_id,
+                user: self.user.key(),
+            }
+            .encode(),
+            on_revert_gas_limit: ON_REVERT_GAS_LIMIT,
+        };
+
+        let payload_len = message_data.len() + revert_options.revert_message.len();
+        if payload_len > gateway::MAX_DEPOSIT_PAYLOAD_SIZE {
+            msg!(
+                "Message and revert message are {} bytes, the gateway accepts at most {}",
+                payload_len,
+                gateway::MAX_DEPOSIT_PAYLOAD_SIZE
+            );
+            return err!(ErrorCode::MessageTooLarge);
+        }
+
+        record_outbound_transfer(
+            &self.outbound_transfer,
+            &self.user,
+            &self.system_program,
+            outbound_transfer_bump,
+            &OutboundTransfer {
+                message: cross_chain_msg.clone(),
+                user: self.user.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+                completed: false,
+            },
+        )?;
+
+        let message_hash = hash(&message_data).to_bytes();
+
+        let gateway_cpi_accounts = gateway::cpi::accounts::Deposit {
+            signer: self.user.to_account_info(),
+            pda: self.gateway_pda.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(self.gateway_program.to_account_info(), gateway_cpi_accounts);
+
+        // The chain's universal contract receives the call and hands the NFT
+        // to the recipient named in the message.
+        gateway::cpi::deposit_and_call(
+            cpi_ctx,
+            gas_amount,
+            self.destination_config.universal_contract,
+            message_data,
+            Some(revert_options),
+        )?;
+
+        msg!(
+            "NFT batch cross-chain transfer initiated: count={}, nonce={}, dest_chain={}, recipient={:?}",
+            cross_chain_msg.tokens.len(),
+            nonce,
+            destination_chain,
+            recipient
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        Ok(cross_chain_msg
+            .tokens
+            .iter()
+            .zip(escrowed)
+            .map(|(token, escrowed)| CrossChainTransferEvent {
+                mint: token.mint,
+                from_chain: self.pda.chain_id,
+                to_chain: destination_chain,
+                recipient,
+                token_id: token.token_id,
+                timestamp,
+                nonce,
+                message_hash,
+                original_chain: cross_chain_msg.original_chain,
+                owner: self.user.key(),
+                escrowed,
+            })
+            .collect())
+    }
+
+    /// Locks one NFT of the batch, holding it in escrow or burning it as
+    /// `send_nft_cross_chain` does, and returns its entry in the message and
+    /// whether it was escrowed.
+    fn take_nft(&self, accounts: &'info [AccountInfo<'info>]) -> Result<(BatchToken, bool)> {
+        let [nft_mint, nft_record, user_token_account, escrow_token_account] = accounts else {
+            return err!(ErrorCode::InvalidBatch);
+        };
+
+        require_keys_eq!(
+            nft_record.key(),
+            find_nft_record_address(&nft_mint.key()).0,
+            ErrorCode::InvalidBatch
+        );
+        let mut record = Account::<NftRecord>::try_from(nft_record)?;
+        require_keys_eq!(
+            record.collection_mint,
+            self.collection_mint.key(),
+            ErrorCode::CollectionMismatch
+        );
+        require!(!record.locked, ErrorCode::NftAlreadyLocked);
+
+        require_keys_eq!(
+            *nft_mint.owner,
+            self.token_program.key(),
+            ErrorCode::InvalidMint
+        );
+        let mint = InterfaceAccount::<Mint>::try_from(nft_mint)?;
+
+        require_keys_eq!(
+            user_token_account.key(),
+            get_associated_token_address_with_program_id(
+                &self.user.key(),
+                &nft_mint.key(),
+                &self.token_program.key()
+            ),
+            ErrorCode::InvalidBatch
+        );
+        let user_tokens = InterfaceAccount::<TokenAccount>::try_from(user_token_account)?;
+        require_eq!(user_tokens.amount, 1, ErrorCode::InsufficientBalance);
+
+        require_keys_eq!(
+            escrow_token_account.key(),
+            get_associated_token_address_with_program_id(
+                &self.pda.key(),
+                &nft_mint.key(),
+                &self.token_program.key()
+            ),
+            ErrorCode::InvalidBatch
+        );
+
+        let native = record.original_chain == self.pda.chain_id;
+        require!(
+            !(native && self.collection.non_transferable),
+            ErrorCode::NativeNftNotTransferable
+        );
+
+        record.locked = true;
+
+        if native {
+            create_idempotent(CpiContext::new(
+                self.associated_token_program.to_account_info(),
+                Create {
+                    payer: self.user.to_account_info(),
+                    associated_token: escrow_token_account.clone(),
+                    authority: self.pda.to_account_info(),
+                    mint: nft_mint.clone(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+            ))?;
+
+            let transfer_ctx = CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: user_token_account.clone(),
+                    mint: nft_mint.clone(),
+                    to: escrow_token_account.clone(),
+                    authority: self.user.to_account_info(),
+                },
+            );
+            transfer_checked(transfer_ctx, 1, mint.decimals)?;
+
+            record.escrowed = true;
+        } else {
+            let burn_ctx = CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: nft_mint.clone(),
+                    from: user_token_account.clone(),
+                    authority: self.user.to_account_info(),
+                },
+            );
+            burn(burn_ctx, 1)?;
+        }
+
+        record.exit(&crate::ID)?;
+
+        Ok((
+            BatchToken {
+                token_id: record.token_id,
+                mint: nft_mint.key(),
+                uri: self
+                    .collection
+                    .token_uri(record.original_chain, record.token_id),
+            },
+            record.escrowed,
+        ))
+    }
+}
@@ -1,9 +1,17 @@
-use crate::{error::ErrorCode, events::*, state::*};
-use anchor_lang::prelude::*;
-use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount};
-use std::mem::size_of;
+use crate::{error::ErrorCode, events::*, state::*, utils::*};
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+use anchor_spl::{
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    token_interface::{
+        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(destination_chain: u64)]
 pub struct SendNftCrossChain<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
@@ -23,22 +31,46 @@ pub struct SendNftCrossChain<'info> {
     pub nft_record: Account<'info, NftRecord>,
 
     #[account(
-        init,
-        payer = user,
-        space = 8 + size_of::<OutboundTransfer>(),
-        seeds = [b"outbound", nft_mint.key().as_ref(), user.key().as_ref()],
+        seeds = [b"collection", nft_record.collection_mint.as_ref()],
         bump
     )]
-    pub outbound_transfer: Account<'info, OutboundTransfer>,
+    pub collection: Account<'info, Collection>,
 
-    pub nft_mint: Account<'info, Mint>,
+    /// CHECK: Outbound transfer record, created here once the size of the message is known
+    #[account(
+        mut,
+        seeds = [b"outbound", pda.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub outbound_transfer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA-owned escrow ATA for native NFTs, created on first use
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&pda.key(), &nft_mint.key(), &token_program.key())
+    )]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// Config of the destination chain, which must exist to send there
+    #[account(
+        seeds = [b"destination", destination_chain.to_le_bytes().as_ref()],
+        bump
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub destination_config: Account<'info, DestinationConfig>,
 
     /// CHECK: ZetaChain Gateway PDA
     #[account(mut)]
@@ -47,17 +79,24 @@ pub struct SendNftCrossChain<'info> {
     /// CHECK: ZetaChain Gateway Program
     pub gateway_program: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(address = collection.token_program)]
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> SendNftCrossChain<'info> {
     pub fn send_nft_cross_chain(
         &mut self,
+        outbound_transfer_bump: u8,
         destination_chain: u64,
         recipient: [u8; 20],
-    ) -> Result<()> {
+        by_reference: bool,
+        gas_amount: Option<u64>,
+        abort_address: Option<[u8; 20]>,
+    ) -> Result<CrossChainTransferEvent> {
         require!(!self.pda.paused, ErrorCode::ProgramPaused);
+        let gas_amount = self.destination_config.resolve_gas_amount(gas_amount)?;
         require!(!self.nft_record.locked, ErrorCode::NftAlreadyLocked);
         require_eq!(
             self.user_token_account.amount,
@@ -65,38 +104,125 @@ impl<'info> SendNftCrossChain<'info> {
             ErrorCode::InsufficientBalance
         );
 
+        // Native NFTs are held in escrow so their mint keeps its supply and
+        // history; wrapped NFTs are burned and re-minted on return. Native
+        // non-transferable NFTs cannot move into escrow and stay on Solana.
+        let native = self.nft_record.original_chain == self.pda.chain_id;
+        require!(
+            !(native && self.collection.non_transferable),
+            ErrorCode::NativeNftNotTransferable
+        );
+
         self.nft_record.locked = true;
 
-        let burn_ctx = CpiContext::new(
-            self.token_program.to_account_info(),
-            Burn {
-                mint: self.nft_mint.to_account_info(),
-                from: self.user_token_account.to_account_info(),
-                authority: self.user.to_account_info(),
-            },
-        );
-        burn(burn_ctx, 1)?;
+        if native {
+            self.lock_in_escrow()?;
+        } else {
+            let burn_ctx = CpiContext::new(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.nft_mint.to_account_info(),
+                    from: self.user_token_account.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            );
+            burn(burn_ctx, 1)?;
+        }
 
-        let cross_chain_msg = CrossChainMessage {
-            action: CrossChainAction::Transfer,
-            token_id: self.nft_record.token_id,
-            mint: self.nft_mint.key(),
-            original_chain: self.nft_record.original_chain,
-            destination_chain,
-            recipient,
-            name: self.nft_record.name.clone(),
-            description: self.nft_record.description.clone(),
-            image: self.nft_record.image.clone(),
+        let nonce = self.pda.nonce;
+        self.pda.nonce += 1;
+
+        let uri = self
+            .collection
+            .token_uri(self.nft_record.original_chain, self.nft_record.token_id);
+
+        // By reference, only the token URI travels and the destination
+        // rebuilds the metadata from it.
+        let cross_chain_msg = if by_reference {
+            CrossChainMessage {
+                action: CrossChainAction::TransferByReference,
+                nonce,
+                token_id: self.nft_record.token_id,
+                mint: self.nft_mint.key(),
+                collection: self.collection.origin.collection,
+                original_chain: self.nft_record.original_chain,
+                destination_chain,
+                recipient: evm_recipient(&recipient),
+                name: String::new(),
+                description: String::new(),
+                image: String::new(),
+                uri: uri.clone(),
+                tokens: Vec::new(),
+            }
+        } else {
+            CrossChainMessage {
+                action: CrossChainAction::Transfer,
+                nonce,
+                token_id: self.nft_record.token_id,
+                mint: self.nft_mint.key(),
+                collection: self.collection.origin.collection,
+                original_chain: self.nft_record.original_chain,
+                destination_chain,
+                recipient: evm_recipient(&recipient),
+                name: self.nft_record.name.clone(),
+                description: self.nft_record.description.clone(),
+                image: self.nft_record.image.clone(),
+                uri: String::new(),
+                tokens: Vec::new(),
+            }
         };
 
-        let message_data = cross_chain_msg.try_to_vec()?;
+        let config = &self.destination_config;
+        let message_data = match config.encoding {
+            MessageEncoding::Abi => {
+                encode_abi_message(&cross_chain_msg, &config.zrc20, &config.sender_alias, &uri)
+            }
+            MessageEncoding::Borsh => cross_chain_msg.encode(),
+        };
 
-        self.outbound_transfer.set_inner(OutboundTransfer {
-            message: cross_chain_msg.clone(),
-            user: self.user.key(),
-            timestamp: Clock::get()?.unix_timestamp,
-            completed: false,
-        });
+        // A failed transfer comes back to `on_revert` on this program, which
+        // finds the outbound transfer to unwind from the revert message.
+        let revert_options = gateway::RevertOptions {
+            revert_address: crate::ID,
+            abort_address: abort_address.unwrap_or_default(),
+            call_on_revert: true,
+            revert_message: OutboundRevertMessage {
+                outbound_transfer: self.outbound_transfer.key(),
+                nonce,
+                mint: self.nft_mint.key(),
+                token_id: self.nft_record.token_id,
+                user: self.user.key(),
+            }
+            .encode(),
+            on_revert_gas_limit: ON_REVERT_GAS_LIMIT,
+        };
+
+        // The gateway counts the revert message against the same limit. Fail
+        // here with a clear error rather than with its `MemoLengthExceeded`.
+        let payload_len = message_data.len() + revert_options.revert_message.len();
+        if payload_len > gateway::MAX_DEPOSIT_PAYLOAD_SIZE {
+            msg!(
+                "Message and revert message are {} bytes, the gateway accepts at most {}",
+                payload_len,
+                gateway::MAX_DEPOSIT_PAYLOAD_SIZE
+            );
+            return err!(ErrorCode::MessageTooLarge);
+        }
+
+        record_outbound_transfer(
+            &self.outbound_transfer,
+            &self.user,
+            &self.system_program,
+            outbound_transfer_bump,
+            &OutboundTransfer {
+                message: cross_chain_msg.clone(),
+                user: self.user.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+                completed: false,
+            },
+        )?;
+
+        let message_hash = hash(&message_data).to_bytes();
 
         let gateway_cpi_accounts = gateway::cpi::accounts::Deposit {
             signer: self.user.to_account_info(),
@@ -106,25 +232,67 @@ impl<'info> SendNftCrossChain<'info> {
 
         let cpi_ctx = CpiContext::new(self.gateway_program.to_account_info(), gateway_cpi_accounts);
 
-        gateway::cpi::deposit_and_call(cpi_ctx, 1000000, recipient, message_data, None)?;
+        // The chain's universal contract receives the call and hands the NFT
+        // to the recipient named in the message.
+        gateway::cpi::deposit_and_call(
+            cpi_ctx,
+            gas_amount,
+            self.destination_config.universal_contract,
+            message_data,
+            Some(revert_options),
+        )?;
 
         msg!(
             "NFT cross-chain transfer initiated: mint={}, token_id={}, dest_chain={}, recipient={:?}",
             self.nft_mint.key(),
-            self.outbound_transfer.message.token_id,
+            cross_chain_msg.token_id,
             destination_chain,
             recipient
         );
 
-        emit!(CrossChainTransferEvent {
+        let event = CrossChainTransferEvent {
             mint: self.nft_mint.key(),
             from_chain: self.pda.chain_id,
             to_chain: destination_chain,
             recipient,
-            token_id: self.outbound_transfer.message.token_id,
+            token_id: cross_chain_msg.token_id,
             timestamp: Clock::get()?.unix_timestamp,
-        });
+            nonce,
+            message_hash,
+            original_chain: self.nft_record.original_chain,
+            owner: self.user.key(),
+            escrowed: self.nft_record.escrowed,
+        };
+
+        Ok(event)
+    }
+
+    fn lock_in_escrow(&mut self) -> Result<()> {
+        create_idempotent(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            Create {
+                payer: self.user.to_account_info(),
+                associated_token: self.escrow_token_account.to_account_info(),
+                authority: self.pda.to_account_info(),
+                mint: self.nft_mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))?;
+
+        let transfer_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: self.user_token_account.to_account_info(),
+                mint: self.nft_mint.to_account_info(),
+                to: self.escrow_token_account.to_account_info(),
+                authority: self.user.to_account_info(),
+            },
+        );
+        transfer_checked(transfer_ctx, 1, self.nft_mint.decimals)?;
 
+        self.nft_record.escrowed = true;
+        msg!("NFT locked in escrow: {}", self.nft_mint.key());
         Ok(())
     }
 }
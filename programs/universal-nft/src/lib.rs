@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
 
+pub mod client;
 pub mod error;
 pub mod events;
 pub mod instructions;
 pub mod state;
+pub mod utils;
 
 pub use error::ErrorCode;
 pub use events::*;
@@ -18,8 +20,17 @@ pub const GATEWAY_PROGRAM_ID: Pubkey = pubkey!("ZETAjseVjuFsxdRxo6MmTCvqFwb3ZHUx
 pub mod universal_nft {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, chain_id: u64) -> Result<()> {
-        ctx.accounts.initialize(chain_id)
+    // Handlers return the events they produce, emitted here through a self-CPI
+    // so indexers read them from inner instructions rather than from logs.
+
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        chain_id: u64,
+        tss_address: [u8; 20],
+    ) -> Result<()> {
+        let event = ctx.accounts.initialize(chain_id, tss_address)?;
+        emit_cpi!(event);
+        Ok(())
     }
 
     pub fn initialize_collection(
@@ -27,8 +38,20 @@ pub mod universal_nft {
         name: String,
         symbol: String,
         base_uri: String,
+        origin: Option<CollectionOrigin>,
+        non_transferable: bool,
     ) -> Result<()> {
-        ctx.accounts.initialize_collection(name, symbol, base_uri)
+        let event = ctx.accounts.initialize_collection(
+            ctx.bumps.pda,
+            ctx.bumps.collection_group,
+            name,
+            symbol,
+            base_uri,
+            origin,
+            non_transferable,
+        )?;
+        emit_cpi!(event);
+        Ok(())
     }
 
     pub fn mint_nft(
@@ -36,53 +59,211 @@ pub mod universal_nft {
         name: String,
         description: String,
         image: String,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let event = ctx
+            .accounts
+            .mint_nft(ctx.bumps.pda, name, description, image, proof)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn mint_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MintBatch<'info>>,
+        nfts: Vec<MintBatchItem>,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        ctx.accounts
-            .mint_nft(ctx.bumps.pda, name, description, image)
+        let events = ctx
+            .accounts
+            .mint_batch(ctx.bumps.pda, nfts, proof, ctx.remaining_accounts)?;
+        for event in events {
+            emit_cpi!(event);
+        }
+        Ok(())
     }
 
     pub fn send_nft_cross_chain(
         ctx: Context<SendNftCrossChain>,
         destination_chain: u64,
         recipient: [u8; 20],
+        by_reference: bool,
+        gas_amount: Option<u64>,
+        abort_address: Option<[u8; 20]>,
+    ) -> Result<()> {
+        let event = ctx.accounts.send_nft_cross_chain(
+            ctx.bumps.outbound_transfer,
+            destination_chain,
+            recipient,
+            by_reference,
+            gas_amount,
+            abort_address,
+        )?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn send_batch_cross_chain<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SendBatchCrossChain<'info>>,
+        destination_chain: u64,
+        recipient: [u8; 20],
+        gas_amount: Option<u64>,
+        abort_address: Option<[u8; 20]>,
     ) -> Result<()> {
-        ctx.accounts
-            .send_nft_cross_chain(destination_chain, recipient)
+        let events = ctx.accounts.send_batch_cross_chain(
+            ctx.bumps.outbound_transfer,
+            destination_chain,
+            recipient,
+            gas_amount,
+            abort_address,
+            ctx.remaining_accounts,
+        )?;
+        for event in events {
+            emit_cpi!(event);
+        }
+        Ok(())
     }
 
     pub fn receive_nft_cross_chain(
         ctx: Context<ReceiveNftCrossChain>,
         cross_chain_msg: CrossChainMessage,
+        signature: [u8; 64],
+        recovery_id: u8,
     ) -> Result<()> {
-        ctx.accounts
-            .receive_nft_cross_chain(ctx.bumps.pda, cross_chain_msg)
+        let events = ctx.accounts.receive_nft_cross_chain(
+            ctx.bumps.pda,
+            cross_chain_msg,
+            signature,
+            recovery_id,
+        )?;
+        for event in events {
+            match event {
+                InboundEvent::Delivered(event) => emit_cpi!(event),
+                InboundEvent::ReturnedToOrigin(event) => emit_cpi!(event),
+                InboundEvent::WrappedMintCreated(event) => emit_cpi!(event),
+                InboundEvent::Received(event) => emit_cpi!(event),
+            }
+        }
+        Ok(())
     }
 
-    pub fn on_call(
-        ctx: Context<OnCallComplete>,
+    pub fn on_call<'info>(
+        ctx: Context<'_, '_, 'info, 'info, OnCallComplete<'info>>,
         amount: u64,
         sender: [u8; 20],
         data: Vec<u8>,
     ) -> Result<()> {
-        ctx.accounts.on_call(
-            ctx.accounts.gateway_program.key(),
+        let events = ctx.accounts.on_call(
             ctx.bumps.pda,
+            ctx.bumps.connected,
             amount,
             sender,
             data,
-        )
+            ctx.remaining_accounts,
+        )?;
+        for event in events {
+            match event {
+                InboundEvent::Delivered(event) => emit_cpi!(event),
+                InboundEvent::ReturnedToOrigin(event) => emit_cpi!(event),
+                InboundEvent::WrappedMintCreated(event) => emit_cpi!(event),
+                InboundEvent::Received(event) => emit_cpi!(event),
+            }
+        }
+        Ok(())
     }
 
-    pub fn on_revert(
-        ctx: Context<OnRevert>,
+    pub fn on_revert<'info>(
+        ctx: Context<'_, '_, 'info, 'info, OnRevert<'info>>,
         amount: u64,
         sender: Pubkey,
         data: Vec<u8>,
     ) -> Result<()> {
-        ctx.accounts.on_revert(ctx.bumps.pda, amount, sender, data)
+        let events = ctx.accounts.on_revert(
+            ctx.bumps.pda,
+            amount,
+            sender,
+            data,
+            ctx.remaining_accounts,
+        )?;
+        for event in events {
+            emit_cpi!(event);
+        }
+        Ok(())
     }
 
     pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
-        ctx.accounts.set_paused(paused)
+        let event = ctx.accounts.set_paused(paused)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn set_tss_address(ctx: Context<SetTssAddress>, tss_address: [u8; 20]) -> Result<()> {
+        let event = ctx.accounts.set_tss_address(tss_address)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn set_reclaim_timeout(
+        ctx: Context<SetReclaimTimeout>,
+        reclaim_timeout: i64,
+    ) -> Result<()> {
+        let event = ctx.accounts.set_reclaim_timeout(reclaim_timeout)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn set_mint_limits(ctx: Context<SetMintLimits>, mint_limits: MintLimits) -> Result<()> {
+        let event = ctx.accounts.set_mint_limits(mint_limits)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn set_mint_policy(ctx: Context<SetMintPolicy>, mint_policy: MintPolicy) -> Result<()> {
+        let event = ctx.accounts.set_mint_policy(mint_policy)?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    pub fn reclaim_stuck_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReclaimStuckTransfer<'info>>,
+        signature: Option<[u8; 64]>,
+        recovery_id: u8,
+    ) -> Result<()> {
+        let events = ctx.accounts.reclaim_stuck_transfer(
+            ctx.bumps.pda,
+            signature,
+            recovery_id,
+            ctx.remaining_accounts,
+        )?;
+        for event in events {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    pub fn set_destination_config(
+        ctx: Context<SetDestinationConfig>,
+        chain_id: u64,
+        encoding: MessageEncoding,
+        zrc20: [u8; 20],
+        gas_amount: u64,
+        min_gas_amount: u64,
+        max_gas_amount: u64,
+        trusted_sender: [u8; 20],
+        universal_contract: [u8; 20],
+        sender_alias: [u8; 20],
+    ) -> Result<()> {
+        let event = ctx.accounts.set_destination_config(
+            chain_id,
+            encoding,
+            zrc20,
+            gas_amount,
+            min_gas_amount,
+            max_gas_amount,
+            trusted_sender,
+            universal_contract,
+            sender_alias,
+        )?;
+        emit_cpi!(event);
+        Ok(())
     }
 }
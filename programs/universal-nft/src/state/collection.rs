@@ -1,3 +1,4 @@
+use crate::{error::ErrorCode, utils::*};
 use anchor_lang::prelude::*;
 
 #[account]
@@ -7,4 +8,256 @@ pub struct Collection {
     pub symbol: String,
     pub base_uri: String,
     pub total_supply: u64,
+    pub collection_mint: Pubkey,
+    pub origin: CollectionOrigin,
+    /// Token program the collection's NFTs are issued under (SPL Token or Token-2022).
+    pub token_program: Pubkey,
+    /// Whether Token-2022 NFTs of this collection carry the non-transferable extension.
+    pub non_transferable: bool,
+    /// Limits on minting native NFTs, set by the collection authority.
+    pub mint_limits: MintLimits,
+    /// Who may mint the collection's native NFTs.
+    pub mint_policy: MintPolicy,
+}
+
+/// Who may mint a collection's native NFTs. The collection authority always may.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MintPolicy {
+    /// Only the collection authority.
+    #[default]
+    AuthorityOnly,
+    /// Any wallet.
+    Open,
+    /// Wallets in the Merkle tree with `merkle_root`, whose leaves are
+    /// `keccak256(wallet)`.
+    Allowlist { merkle_root: [u8; 32] },
+}
+
+/// Limits on minting a collection's native NFTs. Unset limits do not apply.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintLimits {
+    /// Maximum `total_supply` of the collection.
+    pub max_supply: Option<u64>,
+    /// Unix timestamp from which minting is open.
+    pub mint_start: Option<i64>,
+    /// Unix timestamp from which minting is closed.
+    pub mint_end: Option<i64>,
+    /// Maximum number of NFTs minted to a single wallet.
+    pub max_per_wallet: Option<u64>,
+}
+
+/// Identity of a collection on the chain it was created on. For native
+/// collections this is the local chain id and the collection mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollectionOrigin {
+    pub chain_id: u64,
+    pub collection: Pubkey,
+}
+
+impl Collection {
+    /// Metadata URI of the collection NFT itself.
+    pub fn collection_uri(&self) -> String {
+        format!("{}collection", self.base_uri)
+    }
+
+    /// Metadata URI for a token, namespaced by the chain it was minted on.
+    pub fn token_uri(&self, original_chain: u64, token_id: u64) -> String {
+        format!("{}{}/{}", self.base_uri, original_chain, token_id)
+    }
+
+    /// Checks that the mint policy lets `minter` mint, given its allowlist
+    /// `proof` when the collection has an allowlist.
+    pub fn check_minter(&self, minter: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if *minter == self.authority {
+            return Ok(());
+        }
+
+        match self.mint_policy {
+            MintPolicy::AuthorityOnly => err!(ErrorCode::UnauthorizedMinter),
+            MintPolicy::Open => Ok(()),
+            MintPolicy::Allowlist { merkle_root } => {
+                require!(
+                    verify_merkle_proof(proof, &merkle_root, allowlist_leaf(minter)),
+                    ErrorCode::NotOnAllowlist
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks that `count` more NFTs may be minted at `now`, within the mint
+    /// window and the supply cap.
+    pub fn check_mint_allowed(&self, now: i64, count: u64) -> Result<()> {
+        let limits = &self.mint_limits;
+        if let Some(mint_start) = limits.mint_start {
+            require!(now >= mint_start, ErrorCode::MintNotStarted);
+        }
+        if let Some(mint_end) = limits.mint_end {
+            require!(now < mint_end, ErrorCode::MintEnded);
+        }
+        if let Some(max_supply) = limits.max_supply {
+            require!(
+                self.total_supply
+                    .checked_add(count)
+                    .is_some_and(|supply| supply <= max_supply),
+                ErrorCode::MaxSupplyReached
+            );
+        }
+        Ok(())
+    }
+}
+
+impl MintLimits {
+    /// Checks that the limits are consistent with a collection that has
+    /// already minted `total_supply` NFTs.
+    pub fn validate(&self, total_supply: u64) -> Result<()> {
+        if let (Some(mint_start), Some(mint_end)) = (self.mint_start, self.mint_end) {
+            require!(mint_start < mint_end, ErrorCode::InvalidMintLimits);
+        }
+        if let Some(max_supply) = self.max_supply {
+            require!(max_supply >= total_supply, ErrorCode::InvalidMintLimits);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::keccak;
+
+    fn collection(total_supply: u64, mint_limits: MintLimits) -> Collection {
+        Collection {
+            authority: Pubkey::new_unique(),
+            name: "Universal NFTs".to_string(),
+            symbol: "UNFT".to_string(),
+            base_uri: "https://example.com/".to_string(),
+            total_supply,
+            collection_mint: Pubkey::new_unique(),
+            origin: CollectionOrigin {
+                chain_id: 1,
+                collection: Pubkey::new_unique(),
+            },
+            token_program: Pubkey::new_unique(),
+            non_transferable: false,
+            mint_limits,
+            mint_policy: MintPolicy::default(),
+        }
+    }
+
+    #[test]
+    fn test_check_mint_allowed_without_limits() {
+        let collection = collection(u64::MAX - 1, MintLimits::default());
+
+        assert!(collection.check_mint_allowed(0, 1).is_ok());
+    }
+
+    #[test]
+    fn test_check_mint_allowed_enforces_window() {
+        let collection = collection(
+            0,
+            MintLimits {
+                mint_start: Some(100),
+                mint_end: Some(200),
+                ..MintLimits::default()
+            },
+        );
+
+        assert_eq!(
+            collection.check_mint_allowed(99, 1).unwrap_err(),
+            ErrorCode::MintNotStarted.into()
+        );
+        assert!(collection.check_mint_allowed(100, 1).is_ok());
+        assert!(collection.check_mint_allowed(199, 1).is_ok());
+        assert_eq!(
+            collection.check_mint_allowed(200, 1).unwrap_err(),
+            ErrorCode::MintEnded.into()
+        );
+    }
+
+    #[test]
+    fn test_check_mint_allowed_enforces_max_supply() {
+        let collection = collection(
+            8,
+            MintLimits {
+                max_supply: Some(10),
+                ..MintLimits::default()
+            },
+        );
+
+        assert!(collection.check_mint_allowed(0, 2).is_ok());
+        assert_eq!(
+            collection.check_mint_allowed(0, 3).unwrap_err(),
+            ErrorCode::MaxSupplyReached.into()
+        );
+    }
+
+    #[test]
+    fn test_check_minter_authority_only() {
+        let collection = collection(0, MintLimits::default());
+
+        assert!(collection.check_minter(&collection.authority, &[]).is_ok());
+        assert_eq!(
+            collection
+                .check_minter(&Pubkey::new_unique(), &[])
+                .unwrap_err(),
+            ErrorCode::UnauthorizedMinter.into()
+        );
+    }
+
+    #[test]
+    fn test_check_minter_open() {
+        let collection = Collection {
+            mint_policy: MintPolicy::Open,
+            ..collection(0, MintLimits::default())
+        };
+
+        assert!(collection.check_minter(&Pubkey::new_unique(), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_check_minter_allowlist() {
+        let allowed = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let (allowed_leaf, other_leaf) = (allowlist_leaf(&allowed), allowlist_leaf(&other));
+        let mut pair = [allowed_leaf, other_leaf];
+        pair.sort();
+        let merkle_root = keccak::hashv(&[&pair[0], &pair[1]]).to_bytes();
+        let collection = Collection {
+            mint_policy: MintPolicy::Allowlist { merkle_root },
+            ..collection(0, MintLimits::default())
+        };
+
+        assert!(collection.check_minter(&allowed, &[other_leaf]).is_ok());
+        assert!(collection.check_minter(&collection.authority, &[]).is_ok());
+        assert_eq!(
+            collection
+                .check_minter(&Pubkey::new_unique(), &[allowed_leaf])
+                .unwrap_err(),
+            ErrorCode::NotOnAllowlist.into()
+        );
+    }
+
+    #[test]
+    fn test_validate_mint_limits() {
+        let empty_window = MintLimits {
+            mint_start: Some(200),
+            mint_end: Some(200),
+            ..MintLimits::default()
+        };
+        let below_supply = MintLimits {
+            max_supply: Some(4),
+            ..MintLimits::default()
+        };
+
+        assert_eq!(
+            empty_window.validate(0).unwrap_err(),
+            ErrorCode::InvalidMintLimits.into()
+        );
+        assert_eq!(
+            below_supply.validate(5).unwrap_err(),
+            ErrorCode::InvalidMintLimits.into()
+        );
+        assert!(below_supply.validate(4).is_ok());
+    }
 }
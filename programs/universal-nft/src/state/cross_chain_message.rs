@@ -0,0 +1,582 @@
+use crate::error::ErrorCode;
+use anchor_lang::{prelude::*, solana_program::hash::hash};
+
+/// Magic prefix identifying an encoded universal NFT message.
+pub const MESSAGE_MAGIC: [u8; 4] = *b"UNFT";
+
+/// First versioned wire format: a Borsh `TransferV1`, `TransferByReferenceV1`,
+/// `AckV1`, `TransferBatchV1` or `OutboundRevertMessage` body, depending on
+/// the action.
+pub const MESSAGE_VERSION_1: u8 = 1;
+
+/// Version written by `CrossChainMessage::encode`.
+pub const CURRENT_MESSAGE_VERSION: u8 = MESSAGE_VERSION_1;
+
+/// Length of the envelope header: magic, version and action.
+pub const MESSAGE_HEADER_LEN: usize = MESSAGE_MAGIC.len() + 2;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CrossChainMessage {
+    pub action: CrossChainAction,
+    /// Per-source-chain nonce making every message (and its hash) unique.
+    pub nonce: u64,
+    pub token_id: u64,
+    pub mint: Pubkey,
+    pub collection: Pubkey,
+    pub original_chain: u64,
+    pub destination_chain: u64,
+    /// Recipient on the destination chain: a Solana wallet, or an EVM address
+    /// zero-padded on the left as in the ABI.
+    pub recipient: [u8; 32],
+    pub name: String,
+    pub description: String,
+    pub image: String,
+    /// Metadata URI of a `TransferByReference` message, which carries it in
+    /// place of `name`, `description` and `image`. Empty otherwise.
+    pub uri: String,
+    /// Every NFT of a `TransferBatch` message, whose `token_id`, `mint` and
+    /// `uri` repeat those of the first one. Empty for other actions.
+    pub tokens: Vec<BatchToken>,
+}
+
+/// One NFT of a `TransferBatch` message, sent by reference.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchToken {
+    pub token_id: u64,
+    pub mint: Pubkey,
+    pub uri: String,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossChainAction {
+    Transfer,
+    /// Transfer carrying only a metadata URI, to keep payloads small.
+    TransferByReference,
+    /// Acknowledgement from the destination chain that the transfer with
+    /// `nonce` was delivered. Echoes the acknowledged message's `nonce`,
+    /// `token_id`, `mint`, `original_chain` and `destination_chain`.
+    Ack,
+    /// Transfer of several NFTs of one collection to the same recipient, each
+    /// carried by reference in `tokens`.
+    TransferBatch,
+    /// Revert message handed back to `on_revert` when an outbound transfer
+    /// fails. Its body is an `OutboundRevertMessage`, which is never a
+    /// `CrossChainMessage`.
+    Revert,
+}
+
+impl CrossChainAction {
+    /// Action byte of the envelope header.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            CrossChainAction::Transfer => 0,
+            CrossChainAction::TransferByReference => 1,
+            CrossChainAction::Ack => 2,
+            CrossChainAction::TransferBatch => 3,
+            CrossChainAction::Revert => 4,
+        }
+    }
+
+    pub fn from_u8(action: u8) -> Result<Self> {
+        match action {
+            0 => Ok(CrossChainAction::Transfer),
+            1 => Ok(CrossChainAction::TransferByReference),
+            2 => Ok(CrossChainAction::Ack),
+            3 => Ok(CrossChainAction::TransferBatch),
+            4 => Ok(CrossChainAction::Revert),
+            _ => err!(ErrorCode::UnknownMessageAction),
+        }
+    }
+}
+
+/// Body of a version 1 `Transfer` message.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct TransferV1 {
+    nonce: u64,
+    token_id: u64,
+    mint: Pubkey,
+    collection: Pubkey,
+    original_chain: u64,
+    destination_chain: u64,
+    recipient: [u8; 32],
+    name: String,
+    description: String,
+    image: String,
+}
+
+/// Body of a version 1 `TransferByReference` message.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct TransferByReferenceV1 {
+    nonce: u64,
+    token_id: u64,
+    mint: Pubkey,
+    collection: Pubkey,
+    original_chain: u64,
+    destination_chain: u64,
+    recipient: [u8; 32],
+    uri: String,
+}
+
+/// Body of a version 1 `Ack` message.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct AckV1 {
+    nonce: u64,
+    token_id: u64,
+    mint: Pubkey,
+    original_chain: u64,
+    destination_chain: u64,
+}
+
+/// Body of a version 1 `TransferBatch` message.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+struct TransferBatchV1 {
+    nonce: u64,
+    collection: Pubkey,
+    original_chain: u64,
+    destination_chain: u64,
+    recipient: [u8; 32],
+    tokens: Vec<BatchToken>,
+}
+
+/// Starts an encoded message of the current version with `action`.
+pub fn envelope_header(action: CrossChainAction) -> Vec<u8> {
+    let mut data = Vec::with_capacity(MESSAGE_HEADER_LEN);
+    data.extend_from_slice(&MESSAGE_MAGIC);
+    data.push(CURRENT_MESSAGE_VERSION);
+    data.push(action.to_u8());
+    data
+}
+
+/// Checks the envelope of an encoded message and returns its action and body.
+pub fn open_envelope(data: &[u8]) -> Result<(CrossChainAction, &[u8])> {
+    require!(
+        data.len() >= MESSAGE_HEADER_LEN && data[..MESSAGE_MAGIC.len()] == MESSAGE_MAGIC,
+        ErrorCode::InvalidMessage
+    );
+
+    let version = data[MESSAGE_MAGIC.len()];
+    if version != MESSAGE_VERSION_1 {
+        msg!("Unsupported cross-chain message version: {}", version);
+        return err!(ErrorCode::UnsupportedMessageVersion);
+    }
+
+    let action = CrossChainAction::from_u8(data[MESSAGE_MAGIC.len() + 1])?;
+    Ok((action, &data[MESSAGE_HEADER_LEN..]))
+}
+
+/// Left-pads an EVM address into a message recipient.
+pub fn evm_recipient(address: &[u8; 20]) -> [u8; 32] {
+    let mut recipient = [0u8; 32];
+    recipient[12..].copy_from_slice(address);
+    recipient
+}
+
+impl CrossChainMessage {
+    /// Encodes the message as `magic || version || action || body` using the
+    /// current version.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = envelope_header(self.action);
+
+        // Serializing into a `Vec` cannot fail.
+        match self.action {
+            CrossChainAction::Transfer => TransferV1 {
+                nonce: self.nonce,
+                token_id: self.token_id,
+                mint: self.mint,
+                collection: self.collection,
+                original_chain: self.original_chain,
+                destination_chain: self.destination_chain,
+                recipient: self.recipient,
+                name: self.name.clone(),
+                description: self.description.clone(),
+                image: self.image.clone(),
+            }
+            .serialize(&mut data),
+            CrossChainAction::TransferByReference => TransferByReferenceV1 {
+                nonce: self.nonce,
+                token_id: self.token_id,
+                mint: self.mint,
+                collection: self.collection,
+                original_chain: self.original_chain,
+                destination_chain: self.destination_chain,
+                recipient: self.recipient,
+                uri: self.uri.clone(),
+            }
+            .serialize(&mut data),
+            CrossChainAction::Ack => AckV1 {
+                nonce: self.nonce,
+                token_id: self.token_id,
+                mint: self.mint,
+                original_chain: self.original_chain,
+                destination_chain: self.destination_chain,
+            }
+            .serialize(&mut data),
+            CrossChainAction::TransferBatch => TransferBatchV1 {
+                nonce: self.nonce,
+                collection: self.collection,
+                original_chain: self.original_chain,
+                destination_chain: self.destination_chain,
+                recipient: self.recipient,
+                tokens: self.tokens.clone(),
+            }
+            .serialize(&mut data),
+            // Reverts are encoded by `OutboundRevertMessage` and carry no
+            // transfer body.
+            CrossChainAction::Revert => Ok(()),
+        }
+        .unwrap();
+
+        data
+    }
+
+    /// Decodes an encoded message of any supported version.
+    ///
+    /// Bytes following a known body are ignored, so fields appended to a
+    /// version's body by newer senders do not break older decoders.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let (action, mut body) = open_envelope(data)?;
+
+        match action {
+            CrossChainAction::Transfer => {
+                let body =
+                    TransferV1::deserialize(&mut body).map_err(|_| ErrorCode::InvalidMessage)?;
+                Ok(CrossChainMessage {
+                    action: CrossChainAction::Transfer,
+                    nonce: body.nonce,
+                    token_id: body.token_id,
+                    mint: body.mint,
+                    collection: body.collection,
+                    original_chain: body.original_chain,
+                    destination_chain: body.destination_chain,
+                    recipient: body.recipient,
+                    name: body.name,
+                    description: body.description,
+                    image: body.image,
+                    uri: String::new(),
+                    tokens: Vec::new(),
+                })
+            }
+            CrossChainAction::TransferByReference => {
+                let body = TransferByReferenceV1::deserialize(&mut body)
+                    .map_err(|_| ErrorCode::InvalidMessage)?;
+                Ok(CrossChainMessage {
+                    action: CrossChainAction::TransferByReference,
+                    nonce: body.nonce,
+                    token_id: body.token_id,
+                    mint: body.mint,
+                    collection: body.collection,
+                    original_chain: body.original_chain,
+                    destination_chain: body.destination_chain,
+                    recipient: body.recipient,
+                    name: String::new(),
+                    description: String::new(),
+                    image: String::new(),
+                    uri: body.uri,
+                    tokens: Vec::new(),
+                })
+            }
+            CrossChainAction::Ack => {
+                let body = AckV1::deserialize(&mut body).map_err(|_| ErrorCode::InvalidMessage)?;
+                Ok(CrossChainMessage {
+                    action: CrossChainAction::Ack,
+                    nonce: body.nonce,
+                    token_id: body.token_id,
+                    mint: body.mint,
+                    collection: Pubkey::default(),
+                    original_chain: body.original_chain,
+                    destination_chain: body.destination_chain,
+                    recipient: [0u8; 32],
+                    name: String::new(),
+                    description: String::new(),
+                    image: String::new(),
+                    uri: String::new(),
+                    tokens: Vec::new(),
+                })
+            }
+            CrossChainAction::TransferBatch => {
+                let body = TransferBatchV1::deserialize(&mut body)
+                    .map_err(|_| ErrorCode::InvalidMessage)?;
+                let first = body.tokens.first().ok_or(ErrorCode::InvalidMessage)?;
+                Ok(CrossChainMessage {
+                    action: CrossChainAction::TransferBatch,
+                    nonce: body.nonce,
+                    token_id: first.token_id,
+                    mint: first.mint,
+                    collection: body.collection,
+                    original_chain: body.original_chain,
+                    destination_chain: body.destination_chain,
+                    recipient: body.recipient,
+                    name: String::new(),
+                    description: String::new(),
+                    image: String::new(),
+                    uri: first.uri.clone(),
+                    tokens: body.tokens,
+                })
+            }
+            CrossChainAction::Revert => {
+                msg!("Revert messages are only accepted by on_revert");
+                err!(ErrorCode::InvalidMessage)
+            }
+        }
+    }
+
+    /// Splits a `TransferBatch` message into one `TransferByReference` message
+    /// per NFT, in batch order. Any other message is returned as is.
+    pub fn split_batch(&self) -> Vec<CrossChainMessage> {
+        if self.action != CrossChainAction::TransferBatch {
+            return vec![self.clone()];
+        }
+
+        self.tokens
+            .iter()
+            .map(|token| CrossChainMessage {
+                action: CrossChainAction::TransferByReference,
+                token_id: token.token_id,
+                mint: token.mint,
+                uri: token.uri.clone(),
+                tokens: Vec::new(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// Hash of the encoded message, used to key its inbound receipt.
+    pub fn message_hash(&self) -> [u8; 32] {
+        hash(&self.encode()).to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::OutboundRevertMessage;
+
+    fn transfer_message() -> CrossChainMessage {
+        CrossChainMessage {
+            action: CrossChainAction::Transfer,
+            nonce: 7,
+            token_id: 42,
+            mint: Pubkey::new_unique(),
+            collection: Pubkey::new_unique(),
+            original_chain: 1,
+            destination_chain: 7000,
+            recipient: [0xab; 32],
+            name: "Universal NFT".to_string(),
+            description: "A universal NFT".to_string(),
+            image: "https://example.com/42.png".to_string(),
+            uri: String::new(),
+            tokens: Vec::new(),
+        }
+    }
+
+    fn transfer_by_reference_message() -> CrossChainMessage {
+        CrossChainMessage {
+            action: CrossChainAction::TransferByReference,
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            uri: "https://example.com/1/42".to_string(),
+            ..transfer_message()
+        }
+    }
+
+    fn ack_message() -> CrossChainMessage {
+        CrossChainMessage {
+            action: CrossChainAction::Ack,
+            collection: Pubkey::default(),
+            recipient: [0u8; 32],
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            ..transfer_message()
+        }
+    }
+
+    fn transfer_batch_message() -> CrossChainMessage {
+        let tokens = vec![
+            BatchToken {
+                token_id: 42,
+                mint: Pubkey::new_unique(),
+                uri: "https://example.com/1/42".to_string(),
+            },
+            BatchToken {
+                token_id: 43,
+                mint: Pubkey::new_unique(),
+                uri: "https://example.com/1/43".to_string(),
+            },
+        ];
+        CrossChainMessage {
+            action: CrossChainAction::TransferBatch,
+            token_id: tokens[0].token_id,
+            mint: tokens[0].mint,
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            uri: tokens[0].uri.clone(),
+            tokens,
+            ..transfer_message()
+        }
+    }
+
+    fn assert_same_message(a: &CrossChainMessage, b: &CrossChainMessage) {
+        assert_eq!(a.try_to_vec().unwrap(), b.try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_v1_transfer_round_trip() {
+        let message = transfer_message();
+
+        let data = message.encode();
+        assert_eq!(data[..4], MESSAGE_MAGIC);
+        assert_eq!(data[4], MESSAGE_VERSION_1);
+        assert_eq!(data[5], CrossChainAction::Transfer.to_u8());
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_same_message(&message, &decoded);
+    }
+
+    #[test]
+    fn test_v1_transfer_by_reference_round_trip() {
+        let message = transfer_by_reference_message();
+
+        let data = message.encode();
+        assert_eq!(data[4], MESSAGE_VERSION_1);
+        assert_eq!(data[5], CrossChainAction::TransferByReference.to_u8());
+        assert!(data.len() < transfer_message().encode().len());
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_same_message(&message, &decoded);
+    }
+
+    #[test]
+    fn test_v1_ack_round_trip() {
+        let message = ack_message();
+
+        let data = message.encode();
+        assert_eq!(data[5], CrossChainAction::Ack.to_u8());
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_same_message(&message, &decoded);
+    }
+
+    #[test]
+    fn test_v1_transfer_batch_round_trip() {
+        let message = transfer_batch_message();
+
+        let data = message.encode();
+        assert_eq!(data[5], CrossChainAction::TransferBatch.to_u8());
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_same_message(&message, &decoded);
+    }
+
+    #[test]
+    fn test_v1_revert_round_trip() {
+        let message = OutboundRevertMessage {
+            outbound_transfer: Pubkey::new_unique(),
+            nonce: 7,
+            mint: Pubkey::new_unique(),
+            token_id: 42,
+            user: Pubkey::new_unique(),
+        };
+
+        let data = message.encode();
+        assert_eq!(data[..4], MESSAGE_MAGIC);
+        assert_eq!(data[4], MESSAGE_VERSION_1);
+        assert_eq!(data[5], CrossChainAction::Revert.to_u8());
+
+        let decoded = OutboundRevertMessage::decode(&data).unwrap();
+        assert_eq!(decoded, message);
+
+        // A revert message is not a transfer `on_call` could act on
+        let result = CrossChainMessage::decode(&data);
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+
+    #[test]
+    fn test_decode_rejects_empty_batch() {
+        let message = CrossChainMessage {
+            tokens: Vec::new(),
+            ..transfer_batch_message()
+        };
+
+        let result = CrossChainMessage::decode(&message.encode());
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+
+    #[test]
+    fn test_split_batch() {
+        let message = transfer_batch_message();
+
+        let split = message.split_batch();
+
+        assert_eq!(split.len(), 2);
+        for (single, token) in split.iter().zip(&message.tokens) {
+            assert_eq!(single.action, CrossChainAction::TransferByReference);
+            assert_eq!(single.nonce, message.nonce);
+            assert_eq!(single.token_id, token.token_id);
+            assert_eq!(single.mint, token.mint);
+            assert_eq!(single.uri, token.uri);
+            assert!(single.tokens.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_v1_ignores_appended_fields() {
+        let message = transfer_message();
+        let mut data = message.encode();
+        data.extend_from_slice(&[1, 2, 3, 4]);
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_same_message(&message, &decoded);
+    }
+
+    #[test]
+    fn test_message_hash_ignores_padding() {
+        // A padded copy of a message must map to the same inbound receipt
+        let message = transfer_message();
+        let mut data = message.encode();
+        data.extend_from_slice(&[0; 32]);
+
+        let decoded = CrossChainMessage::decode(&data).unwrap();
+        assert_eq!(decoded.message_hash(), message.message_hash());
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_version() {
+        let mut data = transfer_message().encode();
+        data[4] = CURRENT_MESSAGE_VERSION + 1;
+
+        let result = CrossChainMessage::decode(&data);
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::UnsupportedMessageVersion.into()
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_action() {
+        let mut data = transfer_message().encode();
+        data[5] = u8::MAX;
+
+        let result = CrossChainMessage::decode(&data);
+        assert_eq!(result.unwrap_err(), ErrorCode::UnknownMessageAction.into());
+    }
+
+    #[test]
+    fn test_decode_rejects_missing_magic() {
+        // A bare Borsh message, as sent before the envelope was introduced
+        let data = transfer_message().try_to_vec().unwrap();
+
+        let result = CrossChainMessage::decode(&data);
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_body() {
+        let data = transfer_message().encode();
+
+        let result = CrossChainMessage::decode(&data[..data.len() - 1]);
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Per-destination-chain settings for outbound transfers, set by the program
+/// authority. NFTs can only be sent to chains with a config.
+#[account]
+pub struct DestinationConfig {
+    pub chain_id: u64,
+    /// Wire encoding of messages sent to this chain.
+    pub encoding: MessageEncoding,
+    /// ZRC-20 gas token of the chain, the `destination` of ABI-encoded messages.
+    pub zrc20: [u8; 20],
+    /// Lamports deposited to pay for execution on the chain when the user
+    /// does not supply an amount.
+    pub gas_amount: u64,
+    /// Bounds on a user-supplied gas amount.
+    pub min_gas_amount: u64,
+    pub max_gas_amount: u64,
+    /// Gateway call sender trusted to deliver messages from the chain.
+    pub trusted_sender: [u8; 20],
+    /// Universal NFT contract receiving transfers to the chain, the gateway
+    /// `receiver` of every outbound call. The NFT's recipient only travels
+    /// inside the message.
+    pub universal_contract: [u8; 20],
+    /// Address the chain's contracts know this program by, the `sender` of
+    /// ABI-encoded messages.
+    pub sender_alias: [u8; 20],
+}
+
+impl DestinationConfig {
+    /// Resolves the gas amount of an outbound transfer, using the
+    /// destination's default when the user does not supply one.
+    pub fn resolve_gas_amount(&self, requested: Option<u64>) -> Result<u64> {
+        match requested {
+            Some(amount) => {
+                if amount < self.min_gas_amount || amount > self.max_gas_amount {
+                    msg!(
+                        "Gas amount {} is outside [{}, {}]",
+                        amount,
+                        self.min_gas_amount,
+                        self.max_gas_amount
+                    );
+                    return err!(ErrorCode::GasAmountOutOfRange);
+                }
+                Ok(amount)
+            }
+            None => Ok(self.gas_amount),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageEncoding {
+    /// Versioned Borsh envelope, understood by other Solana deployments.
+    #[default]
+    Borsh,
+    /// Solidity ABI, understood by the EVM universal NFT contracts.
+    Abi,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DestinationConfig {
+        DestinationConfig {
+            chain_id: 1,
+            encoding: MessageEncoding::Abi,
+            zrc20: [0x22; 20],
+            gas_amount: 5_000_000,
+            min_gas_amount: 2_000_000,
+            max_gas_amount: 20_000_000,
+            trusted_sender: [0x33; 20],
+            universal_contract: [0x44; 20],
+            sender_alias: [0x55; 20],
+        }
+    }
+
+    #[test]
+    fn test_resolve_gas_amount_uses_config_default() {
+        let amount = config().resolve_gas_amount(None).unwrap();
+
+        assert_eq!(amount, 5_000_000);
+    }
+
+    #[test]
+    fn test_resolve_gas_amount_accepts_amount_within_bounds() {
+        let amount = config().resolve_gas_amount(Some(20_000_000)).unwrap();
+
+        assert_eq!(amount, 20_000_000);
+    }
+
+    #[test]
+    fn test_resolve_gas_amount_rejects_amount_out_of_bounds() {
+        let too_low = config().resolve_gas_amount(Some(1_999_999));
+        let too_high = config().resolve_gas_amount(Some(20_000_001));
+
+        assert_eq!(too_low.unwrap_err(), ErrorCode::GasAmountOutOfRange.into());
+        assert_eq!(too_high.unwrap_err(), ErrorCode::GasAmountOutOfRange.into());
+    }
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+/// Marks an inbound cross-chain message as processed. Keyed by the hash of the
+/// decoded message re-encoded canonically, so a second delivery of the same
+/// message is rejected however its payload was padded or encoded.
+#[account]
+pub struct InboundReceipt {
+    pub message_hash: [u8; 32],
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub token_id: u64,
+    pub timestamp: i64,
+    pub processed: bool,
+}
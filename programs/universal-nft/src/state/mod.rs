@@ -1,9 +1,19 @@
 pub mod collection;
+pub mod cross_chain_message;
+pub mod destination_config;
+pub mod inbound_receipt;
 pub mod nft_record;
+pub mod origin_record;
 pub mod outbound_transfer;
 pub mod pda;
+pub mod wallet_mint_counter;
 
 pub use collection::*;
+pub use cross_chain_message::*;
+pub use destination_config::*;
+pub use inbound_receipt::*;
 pub use nft_record::*;
+pub use origin_record::*;
 pub use outbound_transfer::*;
 pub use pda::*;
+pub use wallet_mint_counter::*;
@@ -3,9 +3,13 @@ use anchor_lang::prelude::*;
 #[account]
 pub struct NftRecord {
     pub mint: Pubkey,
+    pub collection_mint: Pubkey,
     pub original_chain: u64,
     pub token_id: u64,
     pub locked: bool,
+    /// Whether the token of a locked native NFT sits in the program's escrow
+    /// ATA (as opposed to having been burned).
+    pub escrowed: bool,
     pub name: String,
     pub description: String,
     pub image: String,
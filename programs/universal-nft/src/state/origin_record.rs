@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+/// Index from a universal NFT's origin identity to its local mint.
+///
+/// Keyed by `["origin", original_chain, origin_collection, token_id]`; the
+/// reverse lookup is the mint's `NftRecord`.
+#[account]
+pub struct OriginRecord {
+    pub original_chain: u64,
+    pub origin_collection: Pubkey,
+    pub token_id: u64,
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+}
@@ -1,5 +1,9 @@
+use super::{envelope_header, open_envelope, CrossChainAction, CrossChainMessage};
+use crate::error::ErrorCode;
 use anchor_lang::prelude::*;
 
+/// Record of an outbound transfer, keyed by the message nonce so the same NFT
+/// can be sent again once it returns. Closed when the transfer is reverted.
 #[account]
 pub struct OutboundTransfer {
     pub message: CrossChainMessage,
@@ -8,20 +12,68 @@ pub struct OutboundTransfer {
     pub completed: bool,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct CrossChainMessage {
-    pub action: CrossChainAction,
-    pub token_id: u64,
+/// Gas limit of the ZetaChain transaction reverting a failed outbound transfer.
+pub const ON_REVERT_GAS_LIMIT: u64 = 200_000;
+
+/// Revert message attached to an outbound transfer's `RevertOptions` and handed
+/// back to `on_revert`, identifying the `OutboundTransfer` to unwind. Travels in
+/// the message envelope as a `Revert` action.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OutboundRevertMessage {
+    pub outbound_transfer: Pubkey,
+    pub nonce: u64,
     pub mint: Pubkey,
-    pub original_chain: u64,
-    pub destination_chain: u64,
-    pub recipient: [u8; 20],
-    pub name: String,
-    pub description: String,
-    pub image: String,
+    pub token_id: u64,
+    pub user: Pubkey,
+}
+
+impl OutboundRevertMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = envelope_header(CrossChainAction::Revert);
+        // Serializing into a `Vec` cannot fail.
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let (action, mut body) = open_envelope(data)?;
+        require!(
+            action == CrossChainAction::Revert,
+            ErrorCode::InvalidMessage
+        );
+        Self::deserialize(&mut body).map_err(|_| error!(ErrorCode::InvalidMessage))
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub enum CrossChainAction {
-    Transfer,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revert_message() -> OutboundRevertMessage {
+        OutboundRevertMessage {
+            outbound_transfer: Pubkey::new_unique(),
+            nonce: 7,
+            mint: Pubkey::new_unique(),
+            token_id: 42,
+            user: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_revert_message_round_trip() {
+        let message = revert_message();
+
+        let decoded = OutboundRevertMessage::decode(&message.encode()).unwrap();
+
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn test_decode_revert_message_rejects_cross_chain_message() {
+        // `on_revert` used to receive the outbound message itself
+        let data = [b"UNFT".as_ref(), &[1u8; 200]].concat();
+
+        let result = OutboundRevertMessage::decode(&data);
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
 }
@@ -1,9 +1,18 @@
 use anchor_lang::prelude::*;
 
+/// Seconds after which an outbound transfer that neither arrived nor
+/// reverted may be reclaimed, unless the authority sets another timeout.
+pub const DEFAULT_RECLAIM_TIMEOUT: i64 = 7 * 24 * 60 * 60;
+
 #[account]
 pub struct UniversalNftPda {
     pub authority: Pubkey,
     pub chain_id: u64,
+    /// Nonce of the next outbound message, which also keys its `OutboundTransfer`.
     pub nonce: u64,
+    /// Ethereum address of the ZetaChain TSS that signs inbound messages.
+    pub tss_address: [u8; 20],
     pub paused: bool,
+    /// Seconds an outbound transfer must be pending before it can be reclaimed.
+    pub reclaim_timeout: i64,
 }
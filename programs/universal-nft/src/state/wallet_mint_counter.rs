@@ -0,0 +1,72 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+/// Number of NFTs of a collection minted by one wallet, keyed by
+/// `["wallet_mints", collection_mint, wallet]`. Only kept for collections
+/// with a per-wallet limit.
+#[account]
+pub struct WalletMintCounter {
+    pub collection_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub minted: u64,
+}
+
+impl WalletMintCounter {
+    /// Counts `count` more mints by the wallet, failing if that exceeds
+    /// `max_per_wallet`.
+    pub fn record_mints(&mut self, count: u64, max_per_wallet: Option<u64>) -> Result<()> {
+        let minted = self
+            .minted
+            .checked_add(count)
+            .ok_or(ErrorCode::WalletMintLimitReached)?;
+        if let Some(max_per_wallet) = max_per_wallet {
+            require!(minted <= max_per_wallet, ErrorCode::WalletMintLimitReached);
+        }
+        self.minted = minted;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(minted: u64) -> WalletMintCounter {
+        WalletMintCounter {
+            collection_mint: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            minted,
+        }
+    }
+
+    #[test]
+    fn test_record_mints_within_limit() {
+        let mut counter = counter(1);
+
+        counter.record_mints(2, Some(3)).unwrap();
+
+        assert_eq!(counter.minted, 3);
+    }
+
+    #[test]
+    fn test_record_mints_rejects_exceeding_limit() {
+        let mut counter = counter(2);
+
+        let result = counter.record_mints(2, Some(3));
+
+        assert_eq!(
+            result.unwrap_err(),
+            ErrorCode::WalletMintLimitReached.into()
+        );
+        assert_eq!(counter.minted, 2);
+    }
+
+    #[test]
+    fn test_record_mints_without_limit() {
+        let mut counter = counter(u64::MAX - 1);
+
+        counter.record_mints(1, None).unwrap();
+
+        assert_eq!(counter.minted, u64::MAX);
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{error::ErrorCode, state::*};
+use anchor_lang::prelude::*;
+
+/// Size of a Solidity ABI word.
+const WORD: usize = 32;
+
+/// Number of head words of an ABI-encoded message.
+const ABI_MESSAGE_FIELDS: usize = 14;
+
+/// Number of head words of a message from the EVM universal NFT contracts.
+const EVM_MESSAGE_FIELDS: usize = 5;
+
+/// Fields of a [`CrossChainMessage`] that messages from the EVM universal NFT
+/// contracts leave out, supplied by the receiving program.
+pub struct AbiMessageContext {
+    /// Unique per delivery, as the EVM message has no nonce of its own
+    pub nonce: u64,
+    /// Origin of the collection the NFT arrives in
+    pub origin: CollectionOrigin,
+    /// Chain id of this program
+    pub destination_chain: u64,
+}
+
+/// Encodes a message as the Solidity ABI tuple
+///
+/// ```solidity
+/// (address destination, bytes32 receiver, uint256 tokenId, string uri, address sender,
+///  uint8 action, uint64 nonce, uint64 originalChain, uint64 destinationChain,
+///  bytes32 mint, bytes32 collection, string name, string description, string image)
+/// ```
+///
+/// The first five fields match the message of the EVM universal NFT contracts,
+/// which decode them with `abi.decode(message, (address, address, uint256,
+/// string, address))` and ignore the rest. `destination` is the ZRC-20 gas
+/// token of the destination chain, and `receiver` the message recipient,
+/// which reads as an address when it is a padded EVM address. A Solana
+/// program id does not fit an EVM address, so `sender` is the 20-byte alias
+/// the destination's contracts know this program by.
+///
+/// `uri` is the token URI; for `TransferByReference` messages it is the only
+/// metadata carried and the inline strings are left empty. `TransferBatch`
+/// messages have no ABI encoding, as the EVM contracts take one token per call.
+pub fn encode_abi_message(
+    cross_chain_msg: &CrossChainMessage,
+    destination: &[u8; 20],
+    sender: &[u8; 20],
+    uri: &str,
+) -> Vec<u8> {
+    let mut encoder = AbiEncoder::new(ABI_MESSAGE_FIELDS);
+    encoder.address(destination);
+    encoder.bytes32(&cross_chain_msg.recipient);
+    encoder.uint(cross_chain_msg.token_id);
+    encoder.string(uri);
+    encoder.address(sender);
+    encoder.uint(cross_chain_msg.action.to_u8().into());
+    encoder.uint(cross_chain_msg.nonce);
+    encoder.uint(cross_chain_msg.original_chain);
+    encoder.uint(cross_chain_msg.destination_chain);
+    encoder.bytes32(&cross_chain_msg.mint.to_bytes());
+    encoder.bytes32(&cross_chain_msg.collection.to_bytes());
+    encoder.string(&cross_chain_msg.name);
+    encoder.string(&cross_chain_msg.description);
+    encoder.string(&cross_chain_msg.image);
+    encoder.finish()
+}
+
+/// Decodes a message from the EVM universal NFT contracts, which ZetaChain
+/// forwards to connected chains as the Solidity ABI tuple
+///
+/// ```solidity
+/// (bytes32 receiver, uint256 tokenId, string uri, uint256 gasAmount, address sender)
+/// ```
+///
+/// `receiver` is the recipient's Solana wallet. The NFT travels by reference,
+/// so the message decodes to a `TransferByReference` carrying only the token
+/// URI, and the fields the EVM layout leaves out come from `context`. `mint`
+/// is left default for the caller to resolve from the token id. `gasAmount`
+/// and the EVM `sender` are not used. Token ids must fit in a `u64`; larger
+/// values are rejected rather than truncated.
+pub fn decode_abi_message(data: &[u8], context: &AbiMessageContext) -> Result<CrossChainMessage> {
+    let decoder = AbiDecoder::new(data);
+    decoder.word_at((EVM_MESSAGE_FIELDS - 1) * WORD)?;
+
+    Ok(CrossChainMessage {
+        action: CrossChainAction::TransferByReference,
+        nonce: context.nonce,
+        token_id: decoder.uint(1)?,
+        mint: Pubkey::default(),
+        collection: context.origin.collection,
+        original_chain: context.origin.chain_id,
+        destination_chain: context.destination_chain,
+        recipient: decoder.bytes32(0)?,
+        name: String::new(),
+        description: String::new(),
+        image: String::new(),
+        uri: decoder.string(2)?,
+        tokens: Vec::new(),
+    })
+}
+
+/// Minimal encoder for a tuple of static words and strings.
+struct AbiEncoder {
+    head_len: usize,
+    head: Vec<u8>,
+    tail: Vec<u8>,
+}
+
+impl AbiEncoder {
+    fn new(fields: usize) -> Self {
+        Self {
+            head_len: fields * WORD,
+            head: Vec::with_capacity(fields * WORD),
+            tail: Vec::new(),
+        }
+    }
+
+    fn uint(&mut self, value: u64) {
+        self.head.extend_from_slice(&uint_word(value));
+    }
+
+    fn address(&mut self, address: &[u8; 20]) {
+        self.head.extend_from_slice(&[0u8; WORD - 20]);
+        self.head.extend_from_slice(address);
+    }
+
+    fn bytes32(&mut self, value: &[u8; 32]) {
+        self.head.extend_from_slice(value);
+    }
+
+    fn string(&mut self, value: &str) {
+        let offset = self.head_len + self.tail.len();
+        self.head.extend_from_slice(&uint_word(offset as u64));
+
+        self.tail.extend_from_slice(&uint_word(value.len() as u64));
+        self.tail.extend_from_slice(value.as_bytes());
+        let padding = (WORD - value.len() % WORD) % WORD;
+        self.tail.resize(self.tail.len() + padding, 0);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.head.append(&mut self.tail);
+        self.head
+    }
+}
+
+/// Minimal decoder reading head words and strings of a tuple by index.
+struct AbiDecoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AbiDecoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn word_at(&self, offset: usize) -> Result<&'a [u8]> {
+        let end = offset.checked_add(WORD).ok_or(ErrorCode::InvalidMessage)?;
+        self.data
+            .get(offset..end)
+            .ok_or_else(|| error!(ErrorCode::InvalidMessage))
+    }
+
+    fn uint_at(&self, offset: usize) -> Result<u64> {
+        let word = self.word_at(offset)?;
+        require!(
+            word[..WORD - 8].iter().all(|byte| *byte == 0),
+            ErrorCode::InvalidMessage
+        );
+        Ok(u64::from_be_bytes(word[WORD - 8..].try_into().unwrap()))
+    }
+
+    fn uint(&self, index: usize) -> Result<u64> {
+        self.uint_at(index * WORD)
+    }
+
+    fn bytes32(&self, index: usize) -> Result<[u8; 32]> {
+        Ok(self.word_at(index * WORD)?.try_into().unwrap())
+    }
+
+    fn string(&self, index: usize) -> Result<String> {
+        let offset = usize::try_from(self.uint(index)?).map_err(|_| ErrorCode::InvalidMessage)?;
+        let len = usize::try_from(self.uint_at(offset)?).map_err(|_| ErrorCode::InvalidMessage)?;
+        let start = offset + WORD;
+        let end = start.checked_add(len).ok_or(ErrorCode::InvalidMessage)?;
+        let bytes = self.data.get(start..end).ok_or(ErrorCode::InvalidMessage)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| error!(ErrorCode::InvalidMessage))
+    }
+}
+
+fn uint_word(value: u64) -> [u8; WORD] {
+    let mut word = [0u8; WORD];
+    word[WORD - 8..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer_message() -> CrossChainMessage {
+        CrossChainMessage {
+            action: CrossChainAction::Transfer,
+            nonce: 3,
+            token_id: 42,
+            mint: Pubkey::new_unique(),
+            collection: Pubkey::new_unique(),
+            original_chain: 900,
+            destination_chain: 1,
+            recipient: evm_recipient(&[0x11; 20]),
+            name: "Universal NFT".to_string(),
+            description: "A description longer than a single thirty-two byte ABI word".to_string(),
+            image: String::new(),
+            uri: String::new(),
+            tokens: Vec::new(),
+        }
+    }
+
+    fn context() -> AbiMessageContext {
+        AbiMessageContext {
+            nonce: 9,
+            origin: CollectionOrigin {
+                chain_id: 7001,
+                collection: Pubkey::new_from_array([0x33; 32]),
+            },
+            destination_chain: 900,
+        }
+    }
+
+    /// `abi.encode(bytes32(0x1111..11), 42, "https://example.com/42", 500000,
+    /// address(0x2222..22))` as produced by Solidity.
+    fn evm_payload() -> Vec<u8> {
+        [
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "000000000000000000000000000000000000000000000000000000000000002a",
+            "00000000000000000000000000000000000000000000000000000000000000a0",
+            "000000000000000000000000000000000000000000000000000000000007a120",
+            "0000000000000000000000002222222222222222222222222222222222222222",
+            "0000000000000000000000000000000000000000000000000000000000000016",
+            "68747470733a2f2f6578616d706c652e636f6d2f343200000000000000000000",
+        ]
+        .iter()
+        .flat_map(|word| {
+            (0..WORD).map(move |i| u8::from_str_radix(&word[2 * i..2 * i + 2], 16).unwrap())
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_decode_evm_message() {
+        let decoded = decode_abi_message(&evm_payload(), &context()).unwrap();
+
+        let expected = CrossChainMessage {
+            action: CrossChainAction::TransferByReference,
+            nonce: 9,
+            token_id: 42,
+            mint: Pubkey::default(),
+            collection: Pubkey::new_from_array([0x33; 32]),
+            original_chain: 7001,
+            destination_chain: 900,
+            recipient: [0x11; 32],
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            uri: "https://example.com/42".to_string(),
+            tokens: Vec::new(),
+        };
+        assert_eq!(
+            decoded.try_to_vec().unwrap(),
+            expected.try_to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_abi_message_evm_prefix() {
+        // What `abi.decode(message, (address, address, uint256, string, address))`
+        // reads on the EVM side
+        let message = transfer_message();
+        let uri = "https://example.com/900/42";
+        let data = encode_abi_message(&message, &[0x22; 20], &[0x55; 20], uri);
+
+        assert_eq!(data[..12], [0u8; 12]);
+        assert_eq!(data[12..32], [0x22; 20]);
+        assert_eq!(data[32..64], message.recipient);
+        assert_eq!(data[64..96], uint_word(message.token_id));
+
+        let uri_offset = u64::from_be_bytes(data[120..128].try_into().unwrap()) as usize;
+        assert_eq!(uri_offset, ABI_MESSAGE_FIELDS * WORD);
+        assert_eq!(
+            data[uri_offset..uri_offset + 32],
+            uint_word(uri.len() as u64)
+        );
+        assert_eq!(
+            &data[uri_offset + 32..uri_offset + 32 + uri.len()],
+            uri.as_bytes()
+        );
+
+        assert_eq!(data.len() % WORD, 0);
+    }
+
+    #[test]
+    fn test_abi_message_sender_round_trips() {
+        let data = encode_abi_message(&transfer_message(), &[0x22; 20], &[0x55; 20], "");
+
+        let sender = AbiDecoder::new(&data).bytes32(4).unwrap();
+        assert_eq!(sender[..12], [0u8; 12]);
+        assert_eq!(sender[12..], [0x55; 20]);
+    }
+
+    #[test]
+    fn test_decode_abi_rejects_oversized_token_id() {
+        let mut data = evm_payload();
+        data[WORD] = 1;
+
+        let result = decode_abi_message(&data, &context());
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+
+    #[test]
+    fn test_decode_abi_rejects_truncated_message() {
+        let data = evm_payload();
+
+        let result = decode_abi_message(&data[..data.len() - WORD], &context());
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+
+        let result = decode_abi_message(&data[..4 * WORD], &context());
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidMessage.into());
+    }
+}
@@ -0,0 +1,105 @@
+use super::{escrow::restore_locked_nft, nft_metadata::NftMetadataAccounts};
+use crate::{client::find_nft_record_address, error::ErrorCode, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    metadata::mpl_token_metadata::accounts::{MasterEdition, Metadata as MetadataAccount},
+};
+
+/// Remaining accounts passed for each NFT of a batch after the first, whose
+/// accounts are the instruction's named ones, when the batch is given back to
+/// its sender, in order: the `NftRecord`, the mint, the sender's ATA, the
+/// escrow ATA, the Metaplex metadata and the master edition.
+pub const RESTORE_BATCH_ACCOUNTS_PER_NFT: usize = 6;
+
+/// Checks that `metadata` and `master_edition` are the Metaplex accounts of `mint`.
+pub fn check_metadata_accounts(
+    mint: &Pubkey,
+    metadata: &AccountInfo,
+    master_edition: &AccountInfo,
+) -> Result<()> {
+    require_keys_eq!(
+        metadata.key(),
+        MetadataAccount::find_pda(mint).0,
+        ErrorCode::InvalidBatch
+    );
+    require_keys_eq!(
+        master_edition.key(),
+        MasterEdition::find_pda(mint).0,
+        ErrorCode::InvalidBatch
+    );
+    Ok(())
+}
+
+/// Gives every NFT of a batch but the first back to `owner`, reading their
+/// accounts from `remaining_accounts`.
+///
+/// `accounts` holds the accounts shared by the whole batch; its per-NFT
+/// accounts are replaced by each NFT's own.
+pub fn restore_batch_nfts<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    owner: &Pubkey,
+    tokens: &[BatchToken],
+    remaining_accounts: &'info [AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    require!(
+        remaining_accounts.len() == tokens.len() * RESTORE_BATCH_ACCOUNTS_PER_NFT,
+        ErrorCode::InvalidBatch
+    );
+
+    let token_program = accounts.token_program.key();
+
+    for (token, nft_accounts) in tokens
+        .iter()
+        .zip(remaining_accounts.chunks_exact(RESTORE_BATCH_ACCOUNTS_PER_NFT))
+    {
+        let [nft_record, nft_mint, token_account, escrow, metadata, master_edition] = nft_accounts
+        else {
+            return err!(ErrorCode::InvalidBatch);
+        };
+
+        require_keys_eq!(
+            nft_mint.key(),
+            token.mint,
+            ErrorCode::OutboundTransferMismatch
+        );
+        require_keys_eq!(
+            nft_record.key(),
+            find_nft_record_address(&token.mint).0,
+            ErrorCode::InvalidBatch
+        );
+        require_keys_eq!(
+            token_account.key(),
+            get_associated_token_address_with_program_id(owner, &token.mint, &token_program),
+            ErrorCode::InvalidBatch
+        );
+        require_keys_eq!(
+            escrow.key(),
+            get_associated_token_address_with_program_id(
+                &accounts.authority.key(),
+                &token.mint,
+                &token_program
+            ),
+            ErrorCode::InvalidBatch
+        );
+        check_metadata_accounts(&token.mint, metadata, master_edition)?;
+
+        let mut record = Account::<NftRecord>::try_from(nft_record)?;
+        restore_locked_nft(
+            &NftMetadataAccounts {
+                metadata,
+                master_edition,
+                mint: nft_mint,
+                token: token_account,
+                ..*accounts
+            },
+            escrow,
+            &mut record,
+            signer_seeds,
+        )?;
+        record.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use super::{mint_universal_nft::mint_nft_token, nft_metadata::NftMetadataAccounts};
+use crate::state::NftRecord;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, Create},
+    token_interface::{transfer_checked, TransferChecked},
+};
+
+/// Releases a native NFT held in the `universal_nft` PDA's escrow ATA into
+/// `accounts.token`, creating the recipient's associated token account if
+/// needed.
+pub fn release_from_escrow<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    escrow: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    create_idempotent(CpiContext::new_with_signer(
+        accounts.associated_token_program.clone(),
+        Create {
+            payer: accounts.payer.clone(),
+            associated_token: accounts.token.clone(),
+            authority: accounts.token_owner.clone(),
+            mint: accounts.mint.clone(),
+            system_program: accounts.system_program.clone(),
+            token_program: accounts.token_program.clone(),
+        },
+        signer_seeds,
+    ))?;
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            TransferChecked {
+                from: escrow.clone(),
+                mint: accounts.mint.clone(),
+                to: accounts.token.clone(),
+                authority: accounts.authority.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+        0,
+    )
+}
+
+/// Gives a locked NFT back to `accounts.token` after an outbound transfer that
+/// did not go through, releasing it from escrow or re-minting the burned token.
+pub fn restore_locked_nft<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    escrow: &AccountInfo<'info>,
+    record: &mut NftRecord,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    record.locked = false;
+
+    if record.escrowed {
+        record.escrowed = false;
+        release_from_escrow(accounts, escrow, signer_seeds)
+    } else {
+        mint_nft_token(accounts, signer_seeds)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{error::ErrorCode, GATEWAY_PROGRAM_ID};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+        sysvar::instructions::get_instruction_relative,
+    },
+};
+
+/// Verifies that the current instruction is being executed through a CPI
+/// made directly by the gateway program.
+///
+/// A top-level call, or a CPI from any other program (including one invoked by
+/// the gateway), is rejected: the gateway must be the top-level instruction and
+/// this program must sit right below it on the invocation stack.
+pub fn verify_gateway_caller(instructions_sysvar: &AccountInfo) -> Result<()> {
+    verify_gateway_cpi(instructions_sysvar, get_stack_height())
+}
+
+fn verify_gateway_cpi(instructions_sysvar: &AccountInfo, stack_height: usize) -> Result<()> {
+    require_eq!(
+        stack_height,
+        TRANSACTION_LEVEL_STACK_HEIGHT + 1,
+        ErrorCode::InvalidCaller
+    );
+
+    let top_level_instruction = get_instruction_relative(0, instructions_sysvar)?;
+    require_keys_eq!(
+        top_level_instruction.program_id,
+        GATEWAY_PROGRAM_ID,
+        ErrorCode::InvalidCaller
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedInstruction, ID as INSTRUCTIONS_SYSVAR_ID,
+    };
+
+    fn instructions_sysvar_data(top_level_program: &Pubkey) -> Vec<u8> {
+        construct_instructions_data(&[BorrowedInstruction {
+            program_id: top_level_program,
+            accounts: vec![],
+            data: &[],
+        }])
+    }
+
+    fn verify(top_level_program: &Pubkey, stack_height: usize) -> Result<()> {
+        let mut data = instructions_sysvar_data(top_level_program);
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let instructions_sysvar = AccountInfo::new(
+            &INSTRUCTIONS_SYSVAR_ID,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        verify_gateway_cpi(&instructions_sysvar, stack_height)
+    }
+
+    #[test]
+    fn test_verify_gateway_cpi_success() {
+        // Gateway `execute` at the top level invoking this program directly
+        let result = verify(&GATEWAY_PROGRAM_ID, TRANSACTION_LEVEL_STACK_HEIGHT + 1);
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_verify_gateway_cpi_rejects_top_level_call() {
+        // A spoofed call sent straight to this program
+        let result = verify(&crate::ID, TRANSACTION_LEVEL_STACK_HEIGHT);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidCaller.into());
+    }
+
+    #[test]
+    fn test_verify_gateway_cpi_rejects_other_program() {
+        // A spoofed call made via CPI from an arbitrary program
+        let attacker = Pubkey::new_unique();
+        let result = verify(&attacker, TRANSACTION_LEVEL_STACK_HEIGHT + 1);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidCaller.into());
+    }
+
+    #[test]
+    fn test_verify_gateway_cpi_rejects_nested_cpi() {
+        // The gateway invoked another program, which then invoked this one
+        let result = verify(&GATEWAY_PROGRAM_ID, TRANSACTION_LEVEL_STACK_HEIGHT + 2);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::InvalidCaller.into());
+    }
+}
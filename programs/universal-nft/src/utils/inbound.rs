@@ -0,0 +1,211 @@
+use super::{
+    escrow::release_from_escrow,
+    mint_universal_nft::mint_universal_nft,
+    nft_metadata::{CollectionNftAccounts, NftMetadataAccounts},
+    origin_record::record_origin,
+    wrapped_mint::{find_wrapped_mint_address, WRAPPED_MINT_SEED},
+};
+use crate::{error::ErrorCode, events::*, state::*};
+use anchor_lang::prelude::*;
+
+/// Accounts and settings shared by every NFT an inbound message delivers,
+/// whether it arrives through `on_call` or `receive_nft_cross_chain`.
+pub struct InboundContext<'a, 'info> {
+    /// Collection the NFT arrives in.
+    pub collection: &'a Collection,
+    pub collection_nft: CollectionNftAccounts<'a, 'info>,
+    /// Chain id of this program, telling returning native NFTs from foreign ones.
+    pub chain_id: u64,
+    /// The `universal_nft` PDA and its bump.
+    pub pda: &'a AccountInfo<'info>,
+    pub pda_bump: u8,
+    /// Pays for the accounts the delivery creates.
+    pub payer: &'a AccountInfo<'info>,
+    /// Seeds signing for a PDA `payer`, empty otherwise.
+    pub payer_seeds: &'a [&'a [&'a [u8]]],
+    pub recipient_authority: &'a AccountInfo<'info>,
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+/// Accounts of one inbound NFT.
+pub struct InboundNftAccounts<'a, 'info> {
+    pub nft_mint: &'a AccountInfo<'info>,
+    pub origin_record: &'a AccountInfo<'info>,
+    pub recipient_token_account: &'a AccountInfo<'info>,
+    pub escrow_token_account: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+}
+
+/// Delivers the NFT of `cross_chain_msg`, whose `receipt` has been recorded,
+/// to the recipient. A returning native NFT is released from escrow; a foreign
+/// one is minted into its wrapped mint and `record` rewritten for it. The
+/// caller stores `record` afterwards.
+pub fn receive_inbound_nft<'info>(
+    ctx: &InboundContext<'_, 'info>,
+    nft: &InboundNftAccounts<'_, 'info>,
+    receipt: &InboundReceipt,
+    record: &mut NftRecord,
+    cross_chain_msg: CrossChainMessage,
+    events: &mut Vec<InboundEvent>,
+) -> Result<()> {
+    require!(
+        ctx.collection.origin
+            == CollectionOrigin {
+                chain_id: cross_chain_msg.original_chain,
+                collection: cross_chain_msg.collection,
+            },
+        ErrorCode::CollectionMismatch
+    );
+
+    let native = cross_chain_msg.original_chain == ctx.chain_id;
+    let mut wrapped_mint_bump = None;
+    if native {
+        require_keys_eq!(
+            nft.nft_mint.key(),
+            cross_chain_msg.mint,
+            ErrorCode::InvalidMint
+        );
+        // Native NFTs only leave Solana through escrow, so a return must
+        // find the NFT locked there.
+        require!(record.locked && record.escrowed, ErrorCode::NftNotEscrowed);
+        record.locked = false;
+
+        events.push(InboundEvent::ReturnedToOrigin(NftReturnedToOrigin {
+            mint: cross_chain_msg.mint,
+            token_id: cross_chain_msg.token_id,
+            owner: ctx.recipient_authority.key(),
+            message_hash: receipt.message_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        }));
+        msg!("NFT returned to original chain: {}", cross_chain_msg.mint);
+    } else {
+        // Every foreign NFT maps to exactly one wrapped mint, so it comes
+        // back into the same mint each time it returns to Solana.
+        let (wrapped_mint, bump) = find_wrapped_mint_address(
+            cross_chain_msg.original_chain,
+            &cross_chain_msg.collection,
+            cross_chain_msg.token_id,
+        );
+        require_keys_eq!(nft.nft_mint.key(), wrapped_mint, ErrorCode::InvalidMint);
+        wrapped_mint_bump = Some(bump);
+
+        if nft.nft_mint.data_is_empty() {
+            events.push(InboundEvent::WrappedMintCreated(WrappedMintCreated {
+                mint: nft.nft_mint.key(),
+                collection_mint: ctx.collection_nft.mint.key(),
+                original_chain: cross_chain_msg.original_chain,
+                origin_collection: cross_chain_msg.collection,
+                token_id: cross_chain_msg.token_id,
+                message_hash: receipt.message_hash,
+                timestamp: Clock::get()?.unix_timestamp,
+            }));
+        }
+
+        *record = NftRecord {
+            mint: nft.nft_mint.key(),
+            collection_mint: ctx.collection_nft.mint.key(),
+            original_chain: cross_chain_msg.original_chain,
+            token_id: cross_chain_msg.token_id,
+            locked: false,
+            escrowed: false,
+            name: match cross_chain_msg.action {
+                CrossChainAction::TransferByReference => format!(
+                    "Wrapped {} #{}",
+                    ctx.collection.name, cross_chain_msg.token_id
+                ),
+                _ => format!("Wrapped {}", cross_chain_msg.name),
+            },
+            description: cross_chain_msg.description,
+            image: cross_chain_msg.image,
+        };
+        msg!("Wrapped NFT created: {}", nft.nft_mint.key());
+    }
+
+    record_origin(
+        nft.origin_record,
+        ctx.payer,
+        ctx.system_program,
+        ctx.payer_seeds,
+        &OriginRecord {
+            original_chain: cross_chain_msg.original_chain,
+            origin_collection: cross_chain_msg.collection,
+            token_id: cross_chain_msg.token_id,
+            mint: nft.nft_mint.key(),
+            collection_mint: ctx.collection_nft.mint.key(),
+        },
+    )?;
+
+    let seeds = &[b"universal_nft".as_ref(), &[ctx.pda_bump]];
+    let original_chain = cross_chain_msg.original_chain.to_le_bytes();
+    let token_id = cross_chain_msg.token_id.to_le_bytes();
+    let wrapped_mint_seeds = &[
+        WRAPPED_MINT_SEED,
+        &original_chain,
+        cross_chain_msg.collection.as_ref(),
+        &token_id,
+        &[wrapped_mint_bump.unwrap_or_default()],
+    ];
+    let mut signer_seeds = vec![&seeds[..]];
+    signer_seeds.extend_from_slice(ctx.payer_seeds);
+    // The wrapped mint PDA signs for its own creation on first arrival.
+    if wrapped_mint_bump.is_some() {
+        signer_seeds.push(&wrapped_mint_seeds[..]);
+    }
+
+    // Metadata sent by reference is rebuilt from the URI it points at.
+    let uri = if cross_chain_msg.uri.is_empty() {
+        ctx.collection
+            .token_uri(record.original_chain, record.token_id)
+    } else {
+        cross_chain_msg.uri
+    };
+
+    let accounts = NftMetadataAccounts {
+        token_metadata_program: ctx.token_metadata_program,
+        metadata: nft.metadata,
+        master_edition: nft.master_edition,
+        mint: nft.nft_mint,
+        authority: ctx.pda,
+        payer: ctx.payer,
+        token: nft.recipient_token_account,
+        token_owner: ctx.recipient_authority,
+        system_program: ctx.system_program,
+        sysvar_instructions: ctx.sysvar_instructions,
+        token_program: ctx.token_program,
+        associated_token_program: ctx.associated_token_program,
+    };
+
+    if native {
+        record.escrowed = false;
+        release_from_escrow(&accounts, nft.escrow_token_account, &signer_seeds)?;
+        msg!("NFT released from escrow: {}", nft.nft_mint.key());
+    } else {
+        mint_universal_nft(
+            &accounts,
+            &ctx.collection_nft,
+            record.name.clone(),
+            ctx.collection.symbol.clone(),
+            uri,
+            ctx.collection.non_transferable,
+            &signer_seeds,
+        )?;
+    }
+
+    events.push(InboundEvent::Received(NftReceived {
+        mint: nft.nft_mint.key(),
+        collection_mint: ctx.collection_nft.mint.key(),
+        token_id: record.token_id,
+        original_chain: record.original_chain,
+        owner: ctx.recipient_authority.key(),
+        nonce: receipt.nonce,
+        message_hash: receipt.message_hash,
+        timestamp: Clock::get()?.unix_timestamp,
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anchor_lang::{prelude::*, solana_program::keccak::hashv};
+
+/// Leaf of a wallet in a collection's allowlist Merkle tree.
+pub fn allowlist_leaf(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[wallet.as_ref()]).to_bytes()
+}
+
+/// Verifies that `leaf` is in the Merkle tree with `root`.
+///
+/// Sibling pairs are sorted before hashing, so proofs carry no left/right
+/// flags and match trees built with sorted pairs (as OpenZeppelin's
+/// `MerkleProof` and `merkletreejs` with `sortPairs` do) over keccak-256.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets.iter().map(allowlist_leaf).collect();
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, leaves[3]));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof_rejects_wallet_not_in_tree() {
+        let leaves: Vec<[u8; 32]> = (0..2)
+            .map(|_| allowlist_leaf(&Pubkey::new_unique()))
+            .collect();
+        let root = hash_pair(leaves[0], leaves[1]);
+
+        let outsider = allowlist_leaf(&Pubkey::new_unique());
+
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, outsider));
+        assert!(!verify_merkle_proof(&[], &root, leaves[0]));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof_single_leaf_tree() {
+        let leaf = allowlist_leaf(&Pubkey::new_unique());
+
+        assert!(verify_merkle_proof(&[], &leaf, leaf));
+    }
+}
@@ -0,0 +1,64 @@
+use super::{nft_metadata::*, token_2022::*};
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022;
+
+/// Returns true when NFTs are issued under Token-2022 rather than through
+/// the Metaplex token metadata program.
+pub fn is_token_2022(token_program: &AccountInfo) -> bool {
+    token_program.key() == token_2022::ID
+}
+
+/// Creates the NFT mint and its metadata on first use, then mints the token.
+///
+/// SPL Token NFTs get a Metaplex metadata account and master edition and are
+/// verified in `collection`; Token-2022 NFTs carry their metadata on the mint
+/// and join the collection's token group.
+pub fn mint_universal_nft<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    collection: &CollectionNftAccounts<'_, 'info>,
+    name: String,
+    symbol: String,
+    uri: String,
+    non_transferable: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if is_token_2022(accounts.token_program) {
+        if accounts.mint.data_is_empty() {
+            let group = collection.group.ok_or(ErrorCode::CollectionGroupRequired)?;
+            create_token_2022_nft_mint(
+                accounts,
+                group,
+                name,
+                symbol,
+                uri,
+                non_transferable,
+                signer_seeds,
+            )?;
+        }
+    } else if accounts.metadata.data_is_empty() {
+        create_nft_metadata(
+            accounts,
+            name,
+            symbol,
+            uri,
+            Some(collection.mint.key()),
+            signer_seeds,
+        )?;
+        verify_collection_membership(accounts, collection, signer_seeds)?;
+    }
+
+    mint_nft_token(accounts, signer_seeds)
+}
+
+/// Mints the single token of an existing universal NFT into `accounts.token`.
+pub fn mint_nft_token<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if is_token_2022(accounts.token_program) {
+        mint_token_2022_nft_token(accounts, signer_seeds)
+    } else {
+        mint_metadata_nft_token(accounts, signer_seeds)
+    }
+}
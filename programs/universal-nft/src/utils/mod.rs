@@ -0,0 +1,31 @@
+pub mod abi;
+pub mod batch;
+pub mod escrow;
+pub mod gateway_caller;
+pub mod inbound;
+pub mod merkle;
+pub mod mint_universal_nft;
+pub mod nft_metadata;
+pub mod origin_record;
+pub mod outbound_transfer;
+pub mod program_account;
+pub mod token_2022;
+pub mod tss;
+pub mod wallet_mint_counter;
+pub mod wrapped_mint;
+
+pub use abi::*;
+pub use batch::*;
+pub use escrow::*;
+pub use gateway_caller::*;
+pub use inbound::*;
+pub use merkle::*;
+pub use mint_universal_nft::*;
+pub use nft_metadata::*;
+pub use origin_record::*;
+pub use outbound_transfer::*;
+pub use program_account::*;
+pub use token_2022::*;
+pub use tss::*;
+pub use wallet_mint_counter::*;
+pub use wrapped_mint::*;
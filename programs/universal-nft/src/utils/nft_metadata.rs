@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::{
+    instructions::{CreateV1CpiBuilder, MintV1CpiBuilder, VerifyCollectionV1CpiBuilder},
+    types::{Collection as MetadataCollection, CollectionDetails, PrintSupply, TokenStandard},
+    MAX_NAME_LENGTH,
+};
+
+/// Accounts needed to create and mint a Metaplex-backed universal NFT.
+pub struct NftMetadataAccounts<'a, 'info> {
+    pub token_metadata_program: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    /// The `universal_nft` PDA, used as mint and update authority.
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub token: &'a AccountInfo<'info>,
+    pub token_owner: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub sysvar_instructions: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+/// Accounts of the collection NFT that universal NFTs are verified against.
+pub struct CollectionNftAccounts<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: &'a AccountInfo<'info>,
+    pub master_edition: &'a AccountInfo<'info>,
+    /// Token-2022 group mint of the collection, which Token-2022 NFTs are
+    /// members of. Unused by SPL Token collections.
+    pub group: Option<&'a AccountInfo<'info>>,
+}
+
+/// Creates the metadata account and master edition for `accounts.mint`.
+///
+/// With `collection_mint` set the NFT is created as an (unverified) member of
+/// that collection; without it, the NFT is created as a sized collection NFT.
+/// The mint is created by the token metadata program when it does not exist
+/// yet, in which case it must sign, either as a keypair of the transaction or
+/// as a PDA through `signer_seeds`.
+pub fn create_nft_metadata<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    name: String,
+    symbol: String,
+    uri: String,
+    collection_mint: Option<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut builder = CreateV1CpiBuilder::new(accounts.token_metadata_program);
+
+    match collection_mint {
+        Some(key) => builder.collection(MetadataCollection {
+            verified: false,
+            key,
+        }),
+        None => builder.collection_details(CollectionDetails::V1 { size: 0 }),
+    };
+
+    builder
+        .metadata(accounts.metadata)
+        .master_edition(Some(accounts.master_edition))
+        .mint(accounts.mint, accounts.mint.data_is_empty())
+        .authority(accounts.authority)
+        .payer(accounts.payer)
+        .update_authority(accounts.authority, true)
+        .system_program(accounts.system_program)
+        .sysvar_instructions(accounts.sysvar_instructions)
+        .spl_token_program(Some(accounts.token_program))
+        .name(truncate_utf8(name, MAX_NAME_LENGTH))
+        .symbol(symbol)
+        .uri(uri)
+        .seller_fee_basis_points(0)
+        .token_standard(TokenStandard::NonFungible)
+        .print_supply(PrintSupply::Zero)
+        .invoke_signed(signer_seeds)?;
+
+    Ok(())
+}
+
+/// Mints the single token of a Metaplex NFT into `accounts.token`, creating
+/// the associated token account if needed.
+///
+/// Once a master edition exists the token metadata program owns the mint
+/// authority, so (re-)minting has to go through it rather than `mint_to`.
+pub fn mint_metadata_nft_token<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    MintV1CpiBuilder::new(accounts.token_metadata_program)
+        .token(accounts.token)
+        .token_owner(Some(accounts.token_owner))
+        .metadata(accounts.metadata)
+        .master_edition(Some(accounts.master_edition))
+        .mint(accounts.mint)
+        .authority(accounts.authority)
+        .payer(accounts.payer)
+        .system_program(accounts.system_program)
+        .sysvar_instructions(accounts.sysvar_instructions)
+        .spl_token_program(accounts.token_program)
+        .spl_ata_program(accounts.associated_token_program)
+        .amount(1)
+        .invoke_signed(signer_seeds)?;
+
+    Ok(())
+}
+
+/// Marks the metadata of `accounts.mint` as a verified member of `collection`.
+pub fn verify_collection_membership<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    collection: &CollectionNftAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    VerifyCollectionV1CpiBuilder::new(accounts.token_metadata_program)
+        .authority(accounts.authority)
+        .metadata(accounts.metadata)
+        .collection_mint(collection.mint)
+        .collection_metadata(Some(collection.metadata))
+        .collection_master_edition(Some(collection.master_edition))
+        .system_program(accounts.system_program)
+        .sysvar_instructions(accounts.sysvar_instructions)
+        .invoke_signed(signer_seeds)?;
+
+    Ok(())
+}
+
+fn truncate_utf8(mut value: String, max_len: usize) -> String {
+    if value.len() > max_len {
+        let mut end = max_len;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+    }
+    value
+}
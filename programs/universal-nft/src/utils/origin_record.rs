@@ -0,0 +1,74 @@
+use super::program_account::create_program_account;
+use crate::{error::ErrorCode, state::OriginRecord};
+use anchor_lang::prelude::*;
+
+/// Seed prefix of origin records.
+pub const ORIGIN_RECORD_SEED: &[u8] = b"origin";
+
+/// Finds the origin record of the NFT identified by its origin chain, origin
+/// collection and token id.
+pub fn find_origin_record_address(
+    original_chain: u64,
+    origin_collection: &Pubkey,
+    token_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ORIGIN_RECORD_SEED,
+            &original_chain.to_le_bytes(),
+            origin_collection.as_ref(),
+            &token_id.to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Writes `record` to its origin record account, creating the account first
+/// if needed. `payer_seeds` sign for a PDA `payer` and are empty otherwise.
+///
+/// Used where the record's seeds are only known after decoding the message,
+/// so the account cannot be initialized through account constraints. An
+/// existing record must already point at the same mint.
+pub fn record_origin<'info>(
+    origin_record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    payer_seeds: &[&[&[u8]]],
+    record: &OriginRecord,
+) -> Result<()> {
+    let (address, bump) = find_origin_record_address(
+        record.original_chain,
+        &record.origin_collection,
+        record.token_id,
+    );
+    require_keys_eq!(origin_record.key(), address, ErrorCode::InvalidOriginRecord);
+
+    if origin_record.owner == &crate::ID {
+        let existing = OriginRecord::try_deserialize(&mut &origin_record.data.borrow()[..])?;
+        require_keys_eq!(existing.mint, record.mint, ErrorCode::InvalidMint);
+        return Ok(());
+    }
+
+    let original_chain = record.original_chain.to_le_bytes();
+    let token_id = record.token_id.to_le_bytes();
+    let seeds = &[
+        ORIGIN_RECORD_SEED,
+        &original_chain,
+        record.origin_collection.as_ref(),
+        &token_id,
+        &[bump],
+    ];
+    let mut signer_seeds = payer_seeds.to_vec();
+    signer_seeds.push(&seeds[..]);
+
+    create_program_account(
+        origin_record,
+        payer,
+        system_program,
+        8 + std::mem::size_of::<OriginRecord>(),
+        &signer_seeds,
+    )?;
+
+    record.try_serialize(&mut &mut origin_record.data.borrow_mut()[..])?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use super::program_account::create_program_account;
+use crate::state::OutboundTransfer;
+use anchor_lang::prelude::*;
+
+/// Creates the outbound transfer record at `outbound_transfer`, sized for the
+/// message it holds, and writes `record` to it.
+///
+/// Inline metadata, token URIs and batches make the size vary per transfer,
+/// so the account cannot be initialized through account constraints.
+pub fn record_outbound_transfer<'info>(
+    outbound_transfer: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    outbound_transfer_bump: u8,
+    record: &OutboundTransfer,
+) -> Result<()> {
+    let nonce = record.message.nonce.to_le_bytes();
+    let seeds = &[b"outbound".as_ref(), &nonce, &[outbound_transfer_bump]];
+    create_program_account(
+        outbound_transfer,
+        payer,
+        system_program,
+        8 + record.try_to_vec()?.len(),
+        &[&seeds[..]],
+    )?;
+
+    record.try_serialize(&mut &mut outbound_transfer.data.borrow_mut()[..])
+}
@@ -0,0 +1,277 @@
+use super::nft_metadata::NftMetadataAccounts;
+use crate::error::ErrorCode;
+use anchor_lang::{
+    prelude::*,
+    system_program::{create_account, CreateAccount},
+};
+use anchor_spl::{
+    associated_token::{create_idempotent, Create},
+    token_2022::{
+        initialize_mint2, mint_to,
+        spl_token_2022::{extension::ExtensionType, state::Mint as MintState},
+        InitializeMint2, MintTo,
+    },
+    token_2022_extensions::{
+        group_member_pointer_initialize, group_pointer_initialize, metadata_pointer_initialize,
+        non_transferable_mint_initialize, spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::TokenMetadata, token_group_initialize,
+        token_member_initialize, token_metadata_initialize, GroupMemberPointerInitialize,
+        GroupPointerInitialize, MetadataPointerInitialize, NonTransferableMintInitialize,
+        TokenGroupInitialize, TokenMemberInitialize, TokenMetadataInitialize,
+    },
+    token_interface::Mint,
+};
+
+/// Seed of the Token-2022 group mint of a collection, followed by the
+/// collection mint.
+pub const COLLECTION_GROUP_SEED: &[u8] = b"collection_group";
+
+/// Creates the Token-2022 group mint that a Token-2022 collection's NFTs are
+/// verified members of, through the group pointer and token group
+/// extensions. The Metaplex collection NFT cannot carry these extensions, so
+/// the group lives in its own mint, a PDA signing through `signer_seeds`
+/// together with the `universal_nft` PDA, which becomes its update authority.
+pub fn create_token_2022_collection_group<'info>(
+    group: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let group_len =
+        ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::GroupPointer])?;
+    // The token group is appended on initialization, so fund it upfront.
+    let funded_len = ExtensionType::try_calculate_account_len::<MintState>(&[
+        ExtensionType::GroupPointer,
+        ExtensionType::TokenGroup,
+    ])?;
+
+    create_account(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            CreateAccount {
+                from: payer.clone(),
+                to: group.clone(),
+            },
+            signer_seeds,
+        ),
+        Rent::get()?.minimum_balance(funded_len),
+        group_len as u64,
+        token_program.key,
+    )?;
+
+    group_pointer_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            GroupPointerInitialize {
+                token_program_id: token_program.clone(),
+                mint: group.clone(),
+            },
+        ),
+        Some(authority.key()),
+        Some(group.key()),
+    )?;
+
+    initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            InitializeMint2 {
+                mint: group.clone(),
+            },
+        ),
+        0,
+        authority.key,
+        None,
+    )?;
+
+    token_group_initialize(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TokenGroupInitialize {
+                program_id: token_program.clone(),
+                group: group.clone(),
+                mint: group.clone(),
+                mint_authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        Some(authority.key()),
+        u64::MAX,
+    )
+}
+
+/// Creates a Token-2022 NFT mint carrying its own metadata through the
+/// metadata pointer and token metadata extensions, as a member of the
+/// collection's `group` through the group member pointer and token group
+/// member extensions.
+///
+/// The mint account must sign, either as a keypair of the transaction or as a
+/// PDA through `signer_seeds`. When
+/// `non_transferable` is set the mint also gets the non-transferable
+/// extension, which still allows the owner to burn for cross-chain transfers.
+pub fn create_token_2022_nft_mint<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    group: &AccountInfo<'info>,
+    name: String,
+    symbol: String,
+    uri: String,
+    non_transferable: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let authority = accounts.authority.key();
+    let mint = accounts.mint.key();
+
+    let mut extensions = vec![
+        ExtensionType::MetadataPointer,
+        ExtensionType::GroupMemberPointer,
+    ];
+    if non_transferable {
+        extensions.push(ExtensionType::NonTransferable);
+    }
+    let mint_len = ExtensionType::try_calculate_account_len::<MintState>(&extensions)?;
+    extensions.push(ExtensionType::TokenGroupMember);
+    let member_len = ExtensionType::try_calculate_account_len::<MintState>(&extensions)? - mint_len;
+
+    // The token metadata and group membership are appended to the mint on
+    // initialization, so the account has to be funded for them upfront.
+    let metadata_len = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(authority))?,
+        mint,
+        name: name.clone(),
+        symbol: symbol.clone(),
+        uri: uri.clone(),
+        additional_metadata: vec![],
+    }
+    .tlv_size_of()?;
+
+    create_account(
+        CpiContext::new_with_signer(
+            accounts.system_program.clone(),
+            CreateAccount {
+                from: accounts.payer.clone(),
+                to: accounts.mint.clone(),
+            },
+            signer_seeds,
+        ),
+        Rent::get()?.minimum_balance(mint_len + member_len + metadata_len),
+        mint_len as u64,
+        accounts.token_program.key,
+    )?;
+
+    metadata_pointer_initialize(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            MetadataPointerInitialize {
+                token_program_id: accounts.token_program.clone(),
+                mint: accounts.mint.clone(),
+            },
+        ),
+        Some(authority),
+        Some(mint),
+    )?;
+
+    group_member_pointer_initialize(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            GroupMemberPointerInitialize {
+                token_program_id: accounts.token_program.clone(),
+                mint: accounts.mint.clone(),
+            },
+        ),
+        Some(authority),
+        Some(mint),
+    )?;
+
+    if non_transferable {
+        non_transferable_mint_initialize(CpiContext::new(
+            accounts.token_program.clone(),
+            NonTransferableMintInitialize {
+                token_program_id: accounts.token_program.clone(),
+                mint: accounts.mint.clone(),
+            },
+        ))?;
+    }
+
+    initialize_mint2(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            InitializeMint2 {
+                mint: accounts.mint.clone(),
+            },
+        ),
+        0,
+        &authority,
+        Some(&authority),
+    )?;
+
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            TokenMetadataInitialize {
+                program_id: accounts.token_program.clone(),
+                metadata: accounts.mint.clone(),
+                update_authority: accounts.authority.clone(),
+                mint_authority: accounts.authority.clone(),
+                mint: accounts.mint.clone(),
+            },
+            signer_seeds,
+        ),
+        name,
+        symbol,
+        uri,
+    )?;
+
+    token_member_initialize(CpiContext::new_with_signer(
+        accounts.token_program.clone(),
+        TokenMemberInitialize {
+            program_id: accounts.token_program.clone(),
+            member: accounts.mint.clone(),
+            member_mint: accounts.mint.clone(),
+            member_mint_authority: accounts.authority.clone(),
+            group: group.clone(),
+            group_update_authority: accounts.authority.clone(),
+        },
+        signer_seeds,
+    ))
+}
+
+/// Mints the single token of a Token-2022 NFT into `accounts.token`, creating
+/// the associated token account if needed.
+///
+/// The `universal_nft` PDA keeps the mint authority so wrapped NFTs can be
+/// minted again after being burned on their way out, so the supply is checked
+/// to be zero here to keep every NFT at a single token.
+pub fn mint_token_2022_nft_token<'info>(
+    accounts: &NftMetadataAccounts<'_, 'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let supply = Mint::try_deserialize(&mut &accounts.mint.try_borrow_data()?[..])?.supply;
+    require_eq!(supply, 0, ErrorCode::NftAlreadyMinted);
+
+    create_idempotent(CpiContext::new_with_signer(
+        accounts.associated_token_program.clone(),
+        Create {
+            payer: accounts.payer.clone(),
+            associated_token: accounts.token.clone(),
+            authority: accounts.token_owner.clone(),
+            mint: accounts.mint.clone(),
+            system_program: accounts.system_program.clone(),
+            token_program: accounts.token_program.clone(),
+        },
+        signer_seeds,
+    ))?;
+
+    mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            MintTo {
+                mint: accounts.mint.clone(),
+                to: accounts.token.clone(),
+                authority: accounts.authority.clone(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )
+}
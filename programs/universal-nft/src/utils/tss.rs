@@ -0,0 +1,55 @@
+use crate::{error::ErrorCode, state::CrossChainMessage};
+use anchor_lang::{prelude::*, solana_program::keccak::hash};
+
+/// Prefix of the hashes the TSS signs for universal NFT messages, distinct
+/// from the gateway's so signatures cannot be replayed across the two.
+pub const TSS_MESSAGE_PREFIX: &[u8] = b"ZETACHAIN_UNIVERSAL_NFT";
+
+/// Tag separating reclaim proofs from inbound message authorizations.
+pub const RECLAIM_PROOF_TAG: &[u8] = b"RECLAIM";
+
+/// Hash the TSS signs to authorize delivery of `cross_chain_msg` on `chain_id`.
+pub fn inbound_message_hash(
+    chain_id: u64,
+    cross_chain_msg: &CrossChainMessage,
+) -> Result<[u8; 32]> {
+    let mut concatenated_buffer = Vec::new();
+
+    concatenated_buffer.extend_from_slice(TSS_MESSAGE_PREFIX);
+    concatenated_buffer.extend_from_slice(&chain_id.to_be_bytes());
+    concatenated_buffer.extend_from_slice(&cross_chain_msg.encode());
+
+    Ok(hash(&concatenated_buffer[..]).to_bytes())
+}
+
+/// Hash the TSS signs to attest that `cross_chain_msg`, sent from `chain_id`,
+/// was never executed, allowing its sender to reclaim the NFT.
+pub fn reclaim_proof_hash(chain_id: u64, cross_chain_msg: &CrossChainMessage) -> Result<[u8; 32]> {
+    let mut concatenated_buffer = Vec::new();
+
+    concatenated_buffer.extend_from_slice(TSS_MESSAGE_PREFIX);
+    concatenated_buffer.extend_from_slice(RECLAIM_PROOF_TAG);
+    concatenated_buffer.extend_from_slice(&chain_id.to_be_bytes());
+    concatenated_buffer.extend_from_slice(&cross_chain_msg.encode());
+
+    Ok(hash(&concatenated_buffer[..]).to_bytes())
+}
+
+/// Recovers the signer's eth address from `signature`, as the gateway does,
+/// and checks it against the configured TSS address.
+pub fn verify_tss_signature(
+    tss_address: &[u8; 20],
+    message_hash: &[u8; 32],
+    recovery_id: u8,
+    signature: &[u8; 64],
+) -> Result<()> {
+    let eth_address = gateway::recover_eth_address(message_hash, recovery_id, signature)
+        .map_err(|_| error!(ErrorCode::InvalidTssSignature))?;
+
+    require!(
+        eth_address == *tss_address,
+        ErrorCode::TssAuthenticationFailed
+    );
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use super::program_account::create_program_account;
+use crate::{client::find_wallet_mint_counter_address, error::ErrorCode, state::WalletMintCounter};
+use anchor_lang::prelude::*;
+
+/// Counts `count` more mints of `collection_mint` by `minter` against
+/// `max_per_wallet`, creating the minter's counter on its first mint with the
+/// minter paying. Collections without a per-wallet limit keep no counters, so
+/// the counter account is only required when a limit is set.
+pub fn record_wallet_mints<'info>(
+    counter: Option<&AccountInfo<'info>>,
+    minter: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    collection_mint: &Pubkey,
+    count: u64,
+    max_per_wallet: Option<u64>,
+) -> Result<()> {
+    if max_per_wallet.is_none() {
+        return Ok(());
+    }
+    let Some(counter) = counter else {
+        return err!(ErrorCode::WalletMintCounterRequired);
+    };
+
+    let mut wallet_mint_counter = if counter.owner == &crate::ID {
+        WalletMintCounter::try_deserialize(&mut &counter.data.borrow()[..])?
+    } else {
+        let (_, bump) = find_wallet_mint_counter_address(collection_mint, minter.key);
+        let seeds = &[
+            b"wallet_mints".as_ref(),
+            collection_mint.as_ref(),
+            minter.key.as_ref(),
+            &[bump],
+        ];
+        create_program_account(
+            counter,
+            minter,
+            system_program,
+            8 + std::mem::size_of::<WalletMintCounter>(),
+            &[&seeds[..]],
+        )?;
+        WalletMintCounter {
+            collection_mint: *collection_mint,
+            wallet: minter.key(),
+            minted: 0,
+        }
+    };
+
+    wallet_mint_counter.record_mints(count, max_per_wallet)?;
+    wallet_mint_counter.try_serialize(&mut &mut counter.data.borrow_mut()[..])
+}
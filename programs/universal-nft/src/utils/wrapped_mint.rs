@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+/// Seed prefix of wrapped NFT mints.
+pub const WRAPPED_MINT_SEED: &[u8] = b"wrapped_mint";
+
+/// Finds the canonical mint representing a foreign NFT on Solana, derived from
+/// the chain it originates from, its origin collection and its token id.
+pub fn find_wrapped_mint_address(
+    original_chain: u64,
+    origin_collection: &Pubkey,
+    token_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WRAPPED_MINT_SEED,
+            &original_chain.to_le_bytes(),
+            origin_collection.as_ref(),
+            &token_id.to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+}
@@ -1,20 +1,294 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { UniversalNft } from "../target/types/universal_nft";
+import { Gateway } from "../target/types/gateway";
 import {
   getAssociatedTokenAddress,
   ASSOCIATED_TOKEN_PROGRAM_ID,
-  TOKEN_PROGRAM_ID
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
-import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  AddressLookupTableAccount,
+  AddressLookupTableProgram,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from "@solana/web3.js";
+import { createHash } from "crypto";
+import { secp256k1 } from "@noble/curves/secp256k1";
+import { keccak_256 } from "@noble/hashes/sha3";
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bT518x1s"
+);
+
+const findMetadataAddress = (mint: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  )[0];
+
+const findMasterEditionAddress = (mint: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+      Buffer.from("edition"),
+    ],
+    TOKEN_METADATA_PROGRAM_ID
+  )[0];
+
+// Fixed test key standing in for the ZetaChain TSS.
+const TSS_PRIVATE_KEY = Buffer.alloc(32, 7);
+const TSS_ADDRESS = Array.from(
+  keccak_256(secp256k1.getPublicKey(TSS_PRIVATE_KEY, false).slice(1)).slice(12)
+);
+
+const signTss = (messageHash: Uint8Array, privateKey: Uint8Array = TSS_PRIVATE_KEY) => {
+  const signature = secp256k1.sign(messageHash, privateKey);
+  return {
+    signature: Array.from(signature.toCompactRawBytes()),
+    recoveryId: signature.recovery,
+  };
+};
+
+type CrossChainMessage = {
+  action:
+    | { transfer: {} }
+    | { transferByReference: {} }
+    | { ack: {} }
+    | { transferBatch: {} };
+  nonce: anchor.BN;
+  tokenId: anchor.BN;
+  mint: PublicKey;
+  collection: PublicKey;
+  originalChain: anchor.BN;
+  destinationChain: anchor.BN;
+  recipient: number[];
+  name: string;
+  description: string;
+  image: string;
+  uri: string;
+  tokens: { tokenId: anchor.BN; mint: PublicKey; uri: string }[];
+};
+
+const u64Bytes = (value: anchor.BN) => value.toArrayLike(Buffer, "le", 8);
+
+const borshString = (value: string) => {
+  const bytes = Buffer.from(value);
+  const length = Buffer.alloc(4);
+  length.writeUInt32LE(bytes.length);
+  return Buffer.concat([length, bytes]);
+};
+
+// Mirrors `CrossChainMessage::encode`: magic, version 1, action, Borsh body.
+const encodeMessage = (msg: CrossChainMessage) => {
+  const header = (action: number) =>
+    Buffer.concat([Buffer.from("UNFT"), Buffer.from([1, action])]);
+  const transferHead = Buffer.concat([
+    u64Bytes(msg.nonce),
+    u64Bytes(msg.tokenId),
+    msg.mint.toBuffer(),
+    msg.collection.toBuffer(),
+    u64Bytes(msg.originalChain),
+    u64Bytes(msg.destinationChain),
+    Buffer.from(msg.recipient),
+  ]);
+
+  if ("transfer" in msg.action) {
+    return Buffer.concat([
+      header(0),
+      transferHead,
+      borshString(msg.name),
+      borshString(msg.description),
+      borshString(msg.image),
+    ]);
+  }
+  if ("transferByReference" in msg.action) {
+    return Buffer.concat([header(1), transferHead, borshString(msg.uri)]);
+  }
+  if ("ack" in msg.action) {
+    return Buffer.concat([
+      header(2),
+      u64Bytes(msg.nonce),
+      u64Bytes(msg.tokenId),
+      msg.mint.toBuffer(),
+      u64Bytes(msg.originalChain),
+      u64Bytes(msg.destinationChain),
+    ]);
+  }
+  throw new Error("Unsupported message action");
+};
+
+// Mirrors `inbound_message_hash`, the hash the TSS signs to deliver `msg`.
+const inboundMessageHash = (chainId: number, msg: CrossChainMessage) =>
+  keccak_256(
+    Buffer.concat([
+      Buffer.from("ZETACHAIN_UNIVERSAL_NFT"),
+      new anchor.BN(chainId).toArrayLike(Buffer, "be", 8),
+      encodeMessage(msg),
+    ])
+  );
+
+// Mirrors `reclaim_proof_hash`, the hash the TSS signs to attest that `msg`,
+// sent from `chainId`, was never executed.
+const reclaimProofHash = (chainId: number, msg: CrossChainMessage) =>
+  keccak_256(
+    Buffer.concat([
+      Buffer.from("ZETACHAIN_UNIVERSAL_NFT"),
+      Buffer.from("RECLAIM"),
+      new anchor.BN(chainId).toArrayLike(Buffer, "be", 8),
+      encodeMessage(msg),
+    ])
+  );
+
+const abiWord = (value: number) => new anchor.BN(value).toArrayLike(Buffer, "be", 32);
+
+// What the EVM universal NFT contracts send to a connected chain:
+// `abi.encode(receiver, tokenId, uri, gasAmount, sender)`.
+const encodeEvmMessage = (
+  receiver: PublicKey,
+  tokenId: number,
+  uri: string,
+  sender: number[]
+) => {
+  const uriBytes = Buffer.from(uri);
+  const uriWords = Buffer.alloc(Math.ceil(uriBytes.length / 32) * 32);
+  uriBytes.copy(uriWords);
+  return Buffer.concat([
+    receiver.toBuffer(),
+    abiWord(tokenId),
+    abiWord(5 * 32),
+    abiWord(0),
+    Buffer.concat([Buffer.alloc(12), Buffer.from(sender)]),
+    abiWord(uriBytes.length),
+    uriWords,
+  ]);
+};
+
+const expectAnchorError = async (action: Promise<unknown>, code: string) => {
+  try {
+    await action;
+  } catch (err) {
+    // Errors of transactions not sent through `rpc()` only carry the logs.
+    const anchorError =
+      err instanceof anchor.AnchorError
+        ? err
+        : anchor.AnchorError.parse(err.logs ?? []);
+    if (anchorError?.error.errorCode.code === code) {
+      return;
+    }
+    throw err;
+  }
+  throw new Error(`Expected the transaction to fail with ${code}`);
+};
 
 describe("universal-nft", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
   const program = anchor.workspace.UniversalNft as Program<UniversalNft>;
+  const gatewayProgram = anchor.workspace.Gateway as Program<Gateway>;
   const payer = provider.wallet as anchor.Wallet;
 
+  const [gatewayPdaAddress] = PublicKey.findProgramAddressSync(
+    [Buffer.from("meta")],
+    gatewayProgram.programId
+  );
+  const [connectedAddress] = PublicKey.findProgramAddressSync(
+    [Buffer.from("connected")],
+    program.programId
+  );
+  const [eventAuthorityAddress] = PublicKey.findProgramAddressSync(
+    [Buffer.from("__event_authority")],
+    program.programId
+  );
+
+  const findCollectionAddress = (collectionMint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("collection"), collectionMint.toBuffer()],
+      program.programId
+    )[0];
+
+  const findWrappedMintAddress = (
+    originalChain: number,
+    originCollection: PublicKey,
+    tokenId: number
+  ) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("wrapped_mint"),
+        new anchor.BN(originalChain).toArrayLike(Buffer, "le", 8),
+        originCollection.toBuffer(),
+        new anchor.BN(tokenId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const findNftRecordAddress = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("nft_record"), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  const findOutboundTransferAddress = (nonce: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("outbound"), u64Bytes(nonce)],
+      program.programId
+    )[0];
+
+  const findDestinationConfigAddress = (chainId: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("destination"), u64Bytes(new anchor.BN(chainId))],
+      program.programId
+    )[0];
+
+  const findInboundReceiptAddress = (msg: CrossChainMessage) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("inbound_receipt"),
+        createHash("sha256").update(encodeMessage(msg)).digest(),
+      ],
+      program.programId
+    )[0];
+
+  const findOriginRecordAddress = (
+    originalChain: number,
+    originCollection: PublicKey,
+    tokenId: number
+  ) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("origin"),
+        new anchor.BN(originalChain).toArrayLike(Buffer, "le", 8),
+        originCollection.toBuffer(),
+        new anchor.BN(tokenId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const findWalletMintCounterAddress = (
+    collectionMint: PublicKey,
+    wallet: PublicKey
+  ) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("wallet_mints"), collectionMint.toBuffer(), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  const findCollectionGroupAddress = (collectionMint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_group"), collectionMint.toBuffer()],
+      program.programId
+    )[0];
+
   // Test accounts
   let authorityKeypair: Keypair;
   let userKeypair: Keypair;
@@ -22,12 +296,461 @@ describe("universal-nft", () => {
   // Program derived addresses
   let pdaAddress: PublicKey;
   let collectionAddress: PublicKey;
+  let collectionMintKeypair: Keypair;
 
   const CHAIN_ID = 1337;
+  const DESTINATION_CHAIN_ID = 7001;
+  // EVM chain configured for Solidity ABI messages.
+  const ABI_DESTINATION_CHAIN_ID = 11155111;
+  const EVM_RECIPIENT = Array.from(Buffer.alloc(20, 0xab));
+  // Universal NFT contract calling this program through the gateway.
+  const EVM_SENDER = Array.from(Buffer.alloc(20, 0xcd));
+  // Lamports the gateway credits with each `on_call`, funding its accounts.
+  const ON_CALL_AMOUNT = 100_000_000;
+
+  // Collection of NFTs native to the destination chain, wrapped on Solana.
+  const ORIGIN_COLLECTION = Keypair.generate().publicKey;
+  let wrappedCollectionMintKeypair: Keypair;
+
+  // Nonce of the next message the tests deliver to the program.
+  let inboundNonce = 0;
+
+  // Mints a native NFT of the test collection to `owner`, signed by `minter`
+  // (the provider wallet by default) with its allowlist `proof`. Collections
+  // with a per-wallet limit need the minter's `walletMintCounter`.
+  const mintNativeNft = async (
+    owner: PublicKey,
+    {
+      walletMintCounter = null as PublicKey | null,
+      minter = null as Keypair | null,
+      proof = [] as number[][],
+    } = {}
+  ) => {
+    const nftMintKeypair = Keypair.generate();
+    const tokenId = (
+      await program.account.collection.fetch(collectionAddress)
+    ).totalSupply.toNumber();
+
+    await program.methods
+      .mintNft("Traveling NFT", "An NFT crossing chains", "https://test.com/image.png", proof)
+      .accountsPartial({
+        payer: minter ? minter.publicKey : payer.publicKey,
+        pda: pdaAddress,
+        collection: collectionAddress,
+        nftRecord: findNftRecordAddress(nftMintKeypair.publicKey),
+        originRecord: findOriginRecordAddress(
+          CHAIN_ID,
+          collectionMintKeypair.publicKey,
+          tokenId
+        ),
+        nftMint: nftMintKeypair.publicKey,
+        recipientTokenAccount: await getAssociatedTokenAddress(
+          nftMintKeypair.publicKey,
+          owner
+        ),
+        recipientAuthority: owner,
+        walletMintCounter,
+        collectionMint: collectionMintKeypair.publicKey,
+        collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          collectionMintKeypair.publicKey
+        ),
+        metadata: findMetadataAddress(nftMintKeypair.publicKey),
+        masterEdition: findMasterEditionAddress(nftMintKeypair.publicKey),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(minter ? [nftMintKeypair, minter] : [nftMintKeypair])
+      .rpc();
+
+    return { mint: nftMintKeypair.publicKey, tokenId };
+  };
+
+  // Sends `mint` from `owner` to an EVM recipient, by reference unless
+  // `byReference` is false, and returns its outbound transfer.
+  const sendNft = async (
+    mint: PublicKey,
+    owner: Keypair,
+    {
+      gasAmount = null as anchor.BN | null,
+      byReference = true,
+      destinationChain = DESTINATION_CHAIN_ID,
+      destinationConfig = findDestinationConfigAddress(destinationChain),
+    } = {}
+  ) => {
+    const { nonce } = await program.account.universalNftPda.fetch(pdaAddress);
+    const outboundTransfer = findOutboundTransferAddress(nonce);
+
+    await program.methods
+      .sendNftCrossChain(
+        new anchor.BN(destinationChain),
+        EVM_RECIPIENT,
+        byReference,
+        gasAmount,
+        null
+      )
+      .accountsPartial({
+        user: owner.publicKey,
+        pda: pdaAddress,
+        nftRecord: findNftRecordAddress(mint),
+        collection: collectionAddress,
+        outboundTransfer,
+        nftMint: mint,
+        userTokenAccount: await getAssociatedTokenAddress(mint, owner.publicKey),
+        escrowTokenAccount: await getAssociatedTokenAddress(mint, pdaAddress, true),
+        destinationConfig,
+        gatewayPda: gatewayPdaAddress,
+        gatewayProgram: gatewayProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    return outboundTransfer;
+  };
+
+  // A TSS-signed message bringing native NFT `mint` back to `recipient`.
+  const returnMessage = (
+    mint: PublicKey,
+    tokenId: number,
+    recipient: PublicKey = userKeypair.publicKey
+  ): CrossChainMessage => ({
+    action: { transferByReference: {} },
+    nonce: new anchor.BN(inboundNonce++),
+    tokenId: new anchor.BN(tokenId),
+    mint,
+    collection: collectionMintKeypair.publicKey,
+    originalChain: new anchor.BN(CHAIN_ID),
+    destinationChain: new anchor.BN(CHAIN_ID),
+    recipient: Array.from(recipient.toBytes()),
+    name: "",
+    description: "",
+    image: "",
+    uri: "",
+    tokens: [],
+  });
+
+  // Delivers `msg` to `recipient` through `receive_nft_cross_chain`.
+  const receiveNft = async (
+    msg: CrossChainMessage,
+    recipient: PublicKey,
+    { signature, recoveryId } = signTss(inboundMessageHash(CHAIN_ID, msg))
+  ) =>
+    program.methods
+      .receiveNftCrossChain(msg, signature, recoveryId)
+      .accountsPartial({
+        payer: payer.publicKey,
+        pda: pdaAddress,
+        collection: collectionAddress,
+        nftRecord: findNftRecordAddress(msg.mint),
+        inboundReceipt: findInboundReceiptAddress(msg),
+        originRecord: findOriginRecordAddress(
+          msg.originalChain.toNumber(),
+          msg.collection,
+          msg.tokenId.toNumber()
+        ),
+        nftMint: msg.mint,
+        recipientTokenAccount: await getAssociatedTokenAddress(msg.mint, recipient),
+        recipientAuthority: recipient,
+        escrowTokenAccount: await getAssociatedTokenAddress(msg.mint, pdaAddress, true),
+        collectionMint: collectionMintKeypair.publicKey,
+        collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          collectionMintKeypair.publicKey
+        ),
+        collectionGroup: null,
+        metadata: findMetadataAddress(msg.mint),
+        masterEdition: findMasterEditionAddress(msg.mint),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const tokenBalance = async (mint: PublicKey, owner: PublicKey) =>
+    (
+      await provider.connection.getTokenAccountBalance(
+        await getAssociatedTokenAddress(mint, owner, true)
+      )
+    ).value.amount;
+
+  // Accounts of `on_call` delivering `msg` to `recipient` in the collection
+  // of `collectionMint`.
+  const onCallAccounts = async (
+    msg: CrossChainMessage,
+    collectionMint: PublicKey,
+    recipient: PublicKey
+  ) => ({
+    pda: pdaAddress,
+    connected: connectedAddress,
+    gatewayPda: gatewayPdaAddress,
+    collection: findCollectionAddress(collectionMint),
+    nftRecord: findNftRecordAddress(msg.mint),
+    inboundReceipt: findInboundReceiptAddress(msg),
+    originRecord: findOriginRecordAddress(
+      msg.originalChain.toNumber(),
+      msg.collection,
+      msg.tokenId.toNumber()
+    ),
+    nftMint: msg.mint,
+    recipientTokenAccount: await getAssociatedTokenAddress(msg.mint, recipient),
+    recipientAuthority: recipient,
+    escrowTokenAccount: await getAssociatedTokenAddress(msg.mint, pdaAddress, true),
+    collectionMint,
+    collectionMetadata: findMetadataAddress(collectionMint),
+    collectionMasterEdition: findMasterEditionAddress(collectionMint),
+    collectionGroup: null,
+    metadata: findMetadataAddress(msg.mint),
+    masterEdition: findMasterEditionAddress(msg.mint),
+    outboundTransfer: null,
+    userAuthority: null,
+    destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID),
+    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  });
+
+  let onCallLookupTable: AddressLookupTableAccount | undefined;
+
+  // Accounts every gateway call into this program passes, kept in an address
+  // lookup table so `execute` transactions fit the size limit.
+  const getOnCallLookupTable = async () => {
+    if (!onCallLookupTable) {
+      const [createIx, address] = AddressLookupTableProgram.createLookupTable({
+        authority: payer.publicKey,
+        payer: payer.publicKey,
+        recentSlot: (await provider.connection.getSlot()) - 1,
+      });
+      const extendIx = AddressLookupTableProgram.extendLookupTable({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        lookupTable: address,
+        addresses: [
+          program.programId,
+          pdaAddress,
+          connectedAddress,
+          eventAuthorityAddress,
+          gatewayPdaAddress,
+          SYSVAR_INSTRUCTIONS_PUBKEY,
+          TOKEN_METADATA_PROGRAM_ID,
+          TOKEN_PROGRAM_ID,
+          ASSOCIATED_TOKEN_PROGRAM_ID,
+          SystemProgram.programId,
+        ],
+      });
+      await provider.sendAndConfirm(new Transaction().add(createIx, extendIx));
+      // A lookup table can only be used from the slot after its extension.
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      onCallLookupTable = (await provider.connection.getAddressLookupTable(address))
+        .value!;
+    }
+    return onCallLookupTable;
+  };
+
+  // Has the gateway deliver `data` to `on_call` with `accounts`, signed by the
+  // test TSS exactly as `execute` verifies it.
+  const executeOnCall = async (
+    data: Buffer,
+    accounts: Awaited<ReturnType<typeof onCallAccounts>>,
+    { sender = EVM_SENDER, amount = ON_CALL_AMOUNT } = {}
+  ) => {
+    const onCallIx = await program.methods
+      .onCall(new anchor.BN(amount), sender, data)
+      .accountsPartial(accounts)
+      .instruction();
+
+    const { nonce } = await gatewayProgram.account.pda.fetch(gatewayPdaAddress);
+    const messageHash = keccak_256(
+      Buffer.concat([
+        Buffer.from("ZETACHAIN"),
+        Buffer.from([5]), // InstructionId::ExecuteSol
+        new anchor.BN(CHAIN_ID).toArrayLike(Buffer, "be", 8),
+        nonce.toArrayLike(Buffer, "be", 8),
+        new anchor.BN(amount).toArrayLike(Buffer, "be", 8),
+        program.programId.toBuffer(),
+        Buffer.from(sender),
+        data,
+      ])
+    );
+    const { signature, recoveryId } = signTss(messageHash);
+
+    const executeIx = await gatewayProgram.methods
+      .execute(
+        new anchor.BN(amount),
+        sender,
+        data,
+        signature,
+        recoveryId,
+        Array.from(messageHash),
+        nonce
+      )
+      .accountsPartial({
+        signer: payer.publicKey,
+        pda: gatewayPdaAddress,
+        destinationProgram: program.programId,
+        destinationProgramPda: connectedAddress,
+      })
+      // The gateway forwards every account to `on_call` as a non-signer.
+      .remainingAccounts(onCallIx.keys.map(key => ({ ...key, isSigner: false })))
+      .instruction();
+
+    return sendGatewayExecute(executeIx, amount);
+  };
+
+  // Has the gateway revert a transfer to `on_revert` with `accounts`, handing
+  // back its revert message `data`.
+  const executeOnRevert = async (
+    data: Buffer,
+    accounts: Awaited<ReturnType<typeof onRevertAccounts>>,
+    { sender, amount = 0 }: { sender: PublicKey; amount?: number }
+  ) => {
+    const onRevertIx = await program.methods
+      .onRevert(new anchor.BN(amount), sender, data)
+      .accountsPartial(accounts)
+      .instruction();
+
+    const { nonce } = await gatewayProgram.account.pda.fetch(gatewayPdaAddress);
+    const messageHash = keccak_256(
+      Buffer.concat([
+        Buffer.from("ZETACHAIN"),
+        Buffer.from([8]), // InstructionId::ExecuteSolRevert
+        new anchor.BN(CHAIN_ID).toArrayLike(Buffer, "be", 8),
+        nonce.toArrayLike(Buffer, "be", 8),
+        new anchor.BN(amount).toArrayLike(Buffer, "be", 8),
+        program.programId.toBuffer(),
+        sender.toBuffer(),
+        data,
+      ])
+    );
+    const { signature, recoveryId } = signTss(messageHash);
+
+    const executeIx = await gatewayProgram.methods
+      .executeRevert(
+        new anchor.BN(amount),
+        sender,
+        data,
+        signature,
+        recoveryId,
+        Array.from(messageHash),
+        nonce
+      )
+      .accountsPartial({
+        signer: payer.publicKey,
+        pda: gatewayPdaAddress,
+        destinationProgram: program.programId,
+        destinationProgramPda: connectedAddress,
+      })
+      .remainingAccounts(onRevertIx.keys.map(key => ({ ...key, isSigner: false })))
+      .instruction();
+
+    return sendGatewayExecute(executeIx, amount);
+  };
+
+  const sendGatewayExecute = async (
+    executeIx: TransactionInstruction,
+    amount: number
+  ) => {
+    // Stands in for the deposits that fund the gateway's outbound calls.
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: gatewayPdaAddress,
+          lamports: amount,
+        })
+      )
+    );
+
+    const { blockhash } = await provider.connection.getLatestBlockhash();
+    const message = new TransactionMessage({
+      payerKey: payer.publicKey,
+      recentBlockhash: blockhash,
+      instructions: [
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 }),
+        executeIx,
+      ],
+    }).compileToV0Message([await getOnCallLookupTable()]);
+    return provider.sendAndConfirm(new VersionedTransaction(message));
+  };
+
+  // `OutboundRevertMessage` of the transfer at `outboundTransfer` in its
+  // `Revert` envelope (action `4`), with `overrides` applied.
+  const revertMessage = async (
+    outboundTransfer: PublicKey,
+    overrides: { mint?: PublicKey; tokenId?: anchor.BN; user?: PublicKey } = {}
+  ) => {
+    const { message, user } = await program.account.outboundTransfer.fetch(
+      outboundTransfer
+    );
+    return Buffer.concat([
+      Buffer.from("UNFT"),
+      Buffer.from([1, 4]),
+      outboundTransfer.toBuffer(),
+      u64Bytes(message.nonce),
+      (overrides.mint ?? message.mint).toBuffer(),
+      u64Bytes(overrides.tokenId ?? message.tokenId),
+      (overrides.user ?? user).toBuffer(),
+    ]);
+  };
+
+  // Accounts of `on_revert` restoring native NFT `mint` to `user`.
+  const onRevertAccounts = async (
+    mint: PublicKey,
+    outboundTransfer: PublicKey,
+    user: PublicKey
+  ) => ({
+    pda: pdaAddress,
+    nftRecord: findNftRecordAddress(mint),
+    outboundTransfer,
+    nftMint: mint,
+    userTokenAccount: await getAssociatedTokenAddress(mint, user),
+    userAuthority: user,
+    escrowTokenAccount: await getAssociatedTokenAddress(mint, pdaAddress, true),
+    metadata: findMetadataAddress(mint),
+    masterEdition: findMasterEditionAddress(mint),
+    sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+  });
+
+  // Events the program emitted through self-CPI in transaction `signature`.
+  const cpiEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const accountKeys = tx!.transaction.message.getAccountKeys({
+      accountKeysFromLookups: tx!.meta!.loadedAddresses,
+    });
+    return tx!.meta!.innerInstructions!.flatMap(({ instructions }) =>
+      instructions
+        .filter(ix => accountKeys.get(ix.programIdIndex)!.equals(program.programId))
+        .map(ix => {
+          // Event instructions carry an 8-byte tag before the event itself.
+          const data = anchor.utils.bytes.bs58.decode(ix.data);
+          return program.coder.events.decode(
+            anchor.utils.bytes.base64.encode(Buffer.from(data.subarray(8)))
+          );
+        })
+        .filter(event => event !== null)
+    );
+  };
 
   before(async () => {
     authorityKeypair = Keypair.generate();
     userKeypair = Keypair.generate();
+    collectionMintKeypair = Keypair.generate();
 
     // Airdrop SOL
     await provider.connection.requestAirdrop(authorityKeypair.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
@@ -41,14 +764,14 @@ describe("universal-nft", () => {
     );
 
     [collectionAddress] = PublicKey.findProgramAddressSync(
-      [Buffer.from("collection")],
+      [Buffer.from("collection"), collectionMintKeypair.publicKey.toBuffer()],
       program.programId
     );
   });
 
   it("Initializes the program", async () => {
     const tx = await program.methods
-      .initialize(new anchor.BN(CHAIN_ID))
+      .initialize(new anchor.BN(CHAIN_ID), TSS_ADDRESS)
       .accountsPartial({
         authority: authorityKeypair.publicKey,
         pda: pdaAddress,
@@ -60,20 +783,107 @@ describe("universal-nft", () => {
     console.log("Initialize transaction:", tx);
   });
 
+  it("Initializes the gateway", async () => {
+    await gatewayProgram.methods
+      .initialize(TSS_ADDRESS, new anchor.BN(CHAIN_ID))
+      .accountsPartial({
+        signer: payer.publicKey,
+        pda: gatewayPdaAddress,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("Configures the destination chain", async () => {
+    const signature = await program.methods
+      .setDestinationConfig(
+        new anchor.BN(DESTINATION_CHAIN_ID),
+        { borsh: {} },
+        Array.from(Buffer.alloc(20, 0x22)),
+        new anchor.BN(1_000_000),
+        new anchor.BN(1_000_000),
+        new anchor.BN(100_000_000),
+        EVM_SENDER,
+        Array.from(Buffer.alloc(20, 0x44)),
+        Array.from(Buffer.alloc(20, 0x55))
+      )
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        pda: pdaAddress,
+        destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authorityKeypair])
+      .rpc();
+
+    const updated = (await cpiEvents(signature)).find(
+      event => event.name === "destinationConfigUpdated"
+    );
+    if (
+      !updated ||
+      updated.data.chainId.toNumber() !== DESTINATION_CHAIN_ID ||
+      !Buffer.from(updated.data.trustedSender).equals(Buffer.from(EVM_SENDER)) ||
+      !Buffer.from(updated.data.universalContract).equals(Buffer.alloc(20, 0x44)) ||
+      !Buffer.from(updated.data.senderAlias).equals(Buffer.alloc(20, 0x55))
+    ) {
+      throw new Error("DestinationConfigUpdated was not emitted for the config");
+    }
+  });
+
   it("Initializes collection", async () => {
     const tx = await program.methods
-      .initializeCollection("Universal NFTs", "UNFT", "https://api.test.com/")
+      .initializeCollection(
+        "Universal NFTs",
+        "UNFT",
+        "https://api.test.com/",
+        null,
+        false
+      )
       .accountsPartial({
         authority: authorityKeypair.publicKey,
+        pda: pdaAddress,
         collection: collectionAddress,
+        collectionMint: collectionMintKeypair.publicKey,
+        collectionTokenAccount: await getAssociatedTokenAddress(
+          collectionMintKeypair.publicKey,
+          pdaAddress,
+          true
+        ),
+        collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          collectionMintKeypair.publicKey
+        ),
+        collectionGroup: null,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
-      .signers([authorityKeypair])
+      .signers([authorityKeypair, collectionMintKeypair])
       .rpc();
 
     console.log("Collection init transaction:", tx);
   });
 
+  it("Opens minting of the collection to every wallet", async () => {
+    await program.methods
+      .setMintPolicy({ open: {} })
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        collection: collectionAddress,
+        collectionMint: collectionMintKeypair.publicKey,
+      })
+      .signers([authorityKeypair])
+      .rpc();
+
+    const collection = await program.account.collection.fetch(collectionAddress);
+    if (!("open" in collection.mintPolicy)) {
+      throw new Error("Collection mint policy was not opened");
+    }
+  });
+
   it("Mints an NFT", async () => {
     const nftMintKeypair = Keypair.generate();
 
@@ -88,15 +898,30 @@ describe("universal-nft", () => {
     );
 
     const tx = await program.methods
-      .mintNft("Test NFT", "A test NFT", "https://test.com/image.png")
+      .mintNft("Test NFT", "A test NFT", "https://test.com/image.png", [])
       .accountsPartial({
         payer: payer.publicKey,
         pda: pdaAddress,
         collection: collectionAddress,
         nftRecord: nftRecordAddress,
+        originRecord: findOriginRecordAddress(
+          CHAIN_ID,
+          collectionMintKeypair.publicKey,
+          0
+        ),
         nftMint: nftMintKeypair.publicKey,
         recipientTokenAccount: recipientTokenAccount,
         recipientAuthority: userKeypair.publicKey,
+        walletMintCounter: null,
+        collectionMint: collectionMintKeypair.publicKey,
+        collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          collectionMintKeypair.publicKey
+        ),
+        metadata: findMetadataAddress(nftMintKeypair.publicKey),
+        masterEdition: findMasterEditionAddress(nftMintKeypair.publicKey),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -105,6 +930,1243 @@ describe("universal-nft", () => {
       .rpc();
 
     console.log("Mint NFT transaction:", tx);
+
+    const minted = (await cpiEvents(tx)).find(event => event.name === "nftMinted");
+    if (
+      !minted ||
+      !minted.data.mint.equals(nftMintKeypair.publicKey) ||
+      !minted.data.owner.equals(userKeypair.publicKey) ||
+      minted.data.tokenId.toNumber() !== 0 ||
+      minted.data.originalChain.toNumber() !== CHAIN_ID
+    ) {
+      throw new Error("NftMinted was not emitted for the NFT");
+    }
+
+    const masterEdition = await provider.connection.getAccountInfo(
+      findMasterEditionAddress(nftMintKeypair.publicKey)
+    );
+    if (!masterEdition?.owner.equals(TOKEN_METADATA_PROGRAM_ID)) {
+      throw new Error("Master edition was not created");
+    }
+
+    const originRecord = await program.account.originRecord.fetch(
+      findOriginRecordAddress(CHAIN_ID, collectionMintKeypair.publicKey, 0)
+    );
+    if (!originRecord.mint.equals(nftMintKeypair.publicKey)) {
+      throw new Error("Origin record does not point at the minted NFT");
+    }
+  });
+
+  it("Rejects a spoofed on_call sent directly to the program", async () => {
+    const nftMintKeypair = Keypair.generate();
+    const data = Buffer.from("spoofed cross-chain message");
+
+    const [inboundReceiptAddress] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("inbound_receipt"),
+        createHash("sha256").update(data).digest(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .onCall(new anchor.BN(0), Array.from(Buffer.alloc(20)), data)
+        .accountsPartial({
+          pda: pdaAddress,
+          connected: connectedAddress,
+          gatewayPda: gatewayPdaAddress,
+          collection: collectionAddress,
+          inboundReceipt: inboundReceiptAddress,
+          nftRecord: PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_record"), nftMintKeypair.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          originRecord: findOriginRecordAddress(
+            CHAIN_ID,
+            collectionMintKeypair.publicKey,
+            1
+          ),
+          nftMint: nftMintKeypair.publicKey,
+          recipientTokenAccount: await getAssociatedTokenAddress(
+            nftMintKeypair.publicKey,
+            userKeypair.publicKey
+          ),
+          recipientAuthority: userKeypair.publicKey,
+          escrowTokenAccount: await getAssociatedTokenAddress(
+            nftMintKeypair.publicKey,
+            pdaAddress,
+            true
+          ),
+          collectionMint: collectionMintKeypair.publicKey,
+          collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+          collectionMasterEdition: findMasterEditionAddress(
+            collectionMintKeypair.publicKey
+          ),
+          metadata: findMetadataAddress(nftMintKeypair.publicKey),
+          masterEdition: findMasterEditionAddress(nftMintKeypair.publicKey),
+          outboundTransfer: null,
+          userAuthority: null,
+          destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID),
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      throw new Error("Spoofed on_call was accepted");
+    } catch (err) {
+      if (!(err instanceof anchor.AnchorError)) {
+        throw err;
+      }
+      if (err.error.errorCode.code !== "InvalidCaller") {
+        throw err;
+      }
+    }
+  });
+
+  it("Mints a batch of NFTs with sequential token ids", async () => {
+    const nftMintKeypairs = [Keypair.generate(), Keypair.generate()];
+    const firstTokenId = (
+      await program.account.collection.fetch(collectionAddress)
+    ).totalSupply.toNumber();
+
+    const remainingAccounts = [];
+    for (const [i, nftMintKeypair] of nftMintKeypairs.entries()) {
+      const mint = nftMintKeypair.publicKey;
+      remainingAccounts.push(
+        { pubkey: mint, isSigner: true, isWritable: true },
+        {
+          pubkey: PublicKey.findProgramAddressSync(
+            [Buffer.from("nft_record"), mint.toBuffer()],
+            program.programId
+          )[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: findOriginRecordAddress(
+            CHAIN_ID,
+            collectionMintKeypair.publicKey,
+            firstTokenId + i
+          ),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: await getAssociatedTokenAddress(mint, userKeypair.publicKey),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: findMetadataAddress(mint), isSigner: false, isWritable: true },
+        {
+          pubkey: findMasterEditionAddress(mint),
+          isSigner: false,
+          isWritable: true,
+        }
+      );
+    }
+
+    await program.methods
+      .mintBatch([
+        { name: "Batch NFT 1", description: "First", image: "https://test.com/1.png" },
+        { name: "Batch NFT 2", description: "Second", image: "https://test.com/2.png" },
+      ], [])
+      .accountsPartial({
+        payer: payer.publicKey,
+        pda: pdaAddress,
+        collection: collectionAddress,
+        recipientAuthority: userKeypair.publicKey,
+        walletMintCounter: null,
+        collectionMint: collectionMintKeypair.publicKey,
+        collectionMetadata: findMetadataAddress(collectionMintKeypair.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          collectionMintKeypair.publicKey
+        ),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions([
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 }),
+      ])
+      .signers(nftMintKeypairs)
+      .rpc();
+
+    for (const [i, nftMintKeypair] of nftMintKeypairs.entries()) {
+      const originRecord = await program.account.originRecord.fetch(
+        findOriginRecordAddress(
+          CHAIN_ID,
+          collectionMintKeypair.publicKey,
+          firstTokenId + i
+        )
+      );
+      if (!originRecord.mint.equals(nftMintKeypair.publicKey)) {
+        throw new Error(`Token id ${firstTokenId + i} is not the batch's NFT ${i}`);
+      }
+    }
+
+    const collection = await program.account.collection.fetch(collectionAddress);
+    if (collection.totalSupply.toNumber() !== firstTokenId + 2) {
+      throw new Error("Collection supply was not advanced by the batch size");
+    }
+  });
+
+  const setMaxPerWallet = (maxPerWallet: anchor.BN | null) =>
+    program.methods
+      .setMintLimits({
+        maxSupply: null,
+        mintStart: null,
+        mintEnd: null,
+        maxPerWallet,
+      })
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        collection: collectionAddress,
+        collectionMint: collectionMintKeypair.publicKey,
+      })
+      .signers([authorityKeypair])
+      .rpc();
+
+  it("Enforces the collection's per-wallet mint limit", async () => {
+    const walletMintCounter = findWalletMintCounterAddress(
+      collectionMintKeypair.publicKey,
+      payer.publicKey
+    );
+    // Mints without a limit keep no counter.
+    if (await provider.connection.getAccountInfo(walletMintCounter)) {
+      throw new Error("A counter was created for a collection without a limit");
+    }
+
+    await setMaxPerWallet(new anchor.BN(1));
+    try {
+      await expectAnchorError(
+        mintNativeNft(userKeypair.publicKey),
+        "WalletMintCounterRequired"
+      );
+
+      await mintNativeNft(userKeypair.publicKey, { walletMintCounter });
+      const counter = await program.account.walletMintCounter.fetch(walletMintCounter);
+      if (!counter.wallet.equals(payer.publicKey) || counter.minted.toNumber() !== 1) {
+        throw new Error("The mint was not counted against the minting wallet");
+      }
+
+      await expectAnchorError(
+        mintNativeNft(userKeypair.publicKey, { walletMintCounter }),
+        "WalletMintLimitReached"
+      );
+    } finally {
+      await setMaxPerWallet(null);
+    }
+  });
+
+  it("Counts an allowlisted minter's mints to every recipient", async () => {
+    const minter = Keypair.generate();
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: minter.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL,
+        })
+      )
+    );
+
+    // A two-wallet allowlist: each leaf's proof is the other leaf.
+    const minterLeaf = Buffer.from(keccak_256(minter.publicKey.toBuffer()));
+    const otherLeaf = Buffer.from(keccak_256(Keypair.generate().publicKey.toBuffer()));
+    const merkleRoot = keccak_256(
+      Buffer.concat([minterLeaf, otherLeaf].sort(Buffer.compare))
+    );
+    const setMintPolicy = (policy: Parameters<typeof program.methods.setMintPolicy>[0]) =>
+      program.methods
+        .setMintPolicy(policy)
+        .accountsPartial({
+          authority: authorityKeypair.publicKey,
+          collection: collectionAddress,
+          collectionMint: collectionMintKeypair.publicKey,
+        })
+        .signers([authorityKeypair])
+        .rpc();
+
+    const mintAs = (recipient: PublicKey) =>
+      mintNativeNft(recipient, {
+        minter,
+        proof: [Array.from(otherLeaf)],
+        walletMintCounter: findWalletMintCounterAddress(
+          collectionMintKeypair.publicKey,
+          minter.publicKey
+        ),
+      });
+
+    await setMintPolicy({ allowlist: { merkleRoot: Array.from(merkleRoot) } });
+    await setMaxPerWallet(new anchor.BN(2));
+    try {
+      await mintAs(Keypair.generate().publicKey);
+      await mintAs(Keypair.generate().publicKey);
+      // A fresh recipient does not reset the minter's count.
+      await expectAnchorError(
+        mintAs(Keypair.generate().publicKey),
+        "WalletMintLimitReached"
+      );
+    } finally {
+      await setMaxPerWallet(null);
+      await setMintPolicy({ open: {} });
+    }
+  });
+
+  it("Mints a non-transferable Token-2022 NFT", async () => {
+    const soulboundCollectionMint = Keypair.generate();
+    const collectionGroup = findCollectionGroupAddress(
+      soulboundCollectionMint.publicKey
+    );
+    const [soulboundCollection] = PublicKey.findProgramAddressSync(
+      [Buffer.from("collection"), soulboundCollectionMint.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeCollection(
+        "Soulbound NFTs",
+        "SBNFT",
+        "https://api.test.com/soulbound/",
+        null,
+        true
+      )
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        pda: pdaAddress,
+        collection: soulboundCollection,
+        collectionMint: soulboundCollectionMint.publicKey,
+        collectionTokenAccount: await getAssociatedTokenAddress(
+          soulboundCollectionMint.publicKey,
+          pdaAddress,
+          true
+        ),
+        collectionMetadata: findMetadataAddress(soulboundCollectionMint.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          soulboundCollectionMint.publicKey
+        ),
+        collectionGroup,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authorityKeypair, soulboundCollectionMint])
+      .rpc();
+
+    const nftMintKeypair = Keypair.generate();
+    const [nftRecordAddress] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nft_record"), nftMintKeypair.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .mintNft(
+        "Soulbound NFT",
+        "A non-transferable NFT",
+        "https://test.com/image.png",
+        []
+      )
+      .accountsPartial({
+        payer: authorityKeypair.publicKey,
+        pda: pdaAddress,
+        collection: soulboundCollection,
+        nftRecord: nftRecordAddress,
+        originRecord: findOriginRecordAddress(
+          CHAIN_ID,
+          soulboundCollectionMint.publicKey,
+          0
+        ),
+        nftMint: nftMintKeypair.publicKey,
+        recipientTokenAccount: await getAssociatedTokenAddress(
+          nftMintKeypair.publicKey,
+          userKeypair.publicKey,
+          false,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        recipientAuthority: userKeypair.publicKey,
+        walletMintCounter: null,
+        collectionMint: soulboundCollectionMint.publicKey,
+        collectionMetadata: findMetadataAddress(soulboundCollectionMint.publicKey),
+        collectionMasterEdition: findMasterEditionAddress(
+          soulboundCollectionMint.publicKey
+        ),
+        collectionGroup,
+        metadata: findMetadataAddress(nftMintKeypair.publicKey),
+        masterEdition: findMasterEditionAddress(nftMintKeypair.publicKey),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authorityKeypair, nftMintKeypair])
+      .rpc();
+
+    console.log("Mint Token-2022 NFT transaction:", tx);
+
+    const mintInfo = await provider.connection.getAccountInfo(
+      nftMintKeypair.publicKey
+    );
+    if (!mintInfo?.owner.equals(TOKEN_2022_PROGRAM_ID)) {
+      throw new Error("NFT mint was not created under Token-2022");
+    }
+    // The group member extension records the collection's group mint.
+    if (!mintInfo.data.includes(collectionGroup.toBuffer())) {
+      throw new Error("NFT mint is not a member of the collection's token group");
+    }
+  });
+
+  it("Initializes a wrapped collection for NFTs from another chain", async () => {
+    wrappedCollectionMintKeypair = Keypair.generate();
+    const collectionMint = wrappedCollectionMintKeypair.publicKey;
+
+    await program.methods
+      .initializeCollection(
+        "Wrapped EVM NFTs",
+        "WEVM",
+        "https://api.test.com/wrapped/",
+        {
+          chainId: new anchor.BN(DESTINATION_CHAIN_ID),
+          collection: ORIGIN_COLLECTION,
+        },
+        false
+      )
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        pda: pdaAddress,
+        collection: findCollectionAddress(collectionMint),
+        collectionMint,
+        collectionTokenAccount: await getAssociatedTokenAddress(
+          collectionMint,
+          pdaAddress,
+          true
+        ),
+        collectionMetadata: findMetadataAddress(collectionMint),
+        collectionMasterEdition: findMasterEditionAddress(collectionMint),
+        collectionGroup: null,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        nftTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authorityKeypair, wrappedCollectionMintKeypair])
+      .rpc();
+  });
+
+  it("Mints a wrapped NFT delivered through the gateway", async () => {
+    const tokenId = 42;
+    const mint = findWrappedMintAddress(DESTINATION_CHAIN_ID, ORIGIN_COLLECTION, tokenId);
+    const msg: CrossChainMessage = {
+      action: { transferByReference: {} },
+      nonce: new anchor.BN(inboundNonce++),
+      tokenId: new anchor.BN(tokenId),
+      mint,
+      collection: ORIGIN_COLLECTION,
+      originalChain: new anchor.BN(DESTINATION_CHAIN_ID),
+      destinationChain: new anchor.BN(CHAIN_ID),
+      recipient: Array.from(userKeypair.publicKey.toBytes()),
+      name: "",
+      description: "",
+      image: "",
+      uri: "https://evm.test/nft/42.json",
+      tokens: [],
+    };
+    const connectedBefore = await provider.connection.getBalance(connectedAddress);
+
+    const signature = await executeOnCall(
+      encodeMessage(msg),
+      await onCallAccounts(
+        msg,
+        wrappedCollectionMintKeypair.publicKey,
+        userKeypair.publicKey
+      )
+    );
+
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The wrapped NFT was not minted to its recipient");
+    }
+    const events = await cpiEvents(signature);
+    const created = events.find(event => event.name === "wrappedMintCreated");
+    const received = events.find(event => event.name === "nftReceived");
+    if (
+      !created ||
+      !created.data.mint.equals(mint) ||
+      !created.data.originCollection.equals(ORIGIN_COLLECTION) ||
+      !received ||
+      !received.data.mint.equals(mint) ||
+      !received.data.owner.equals(userKeypair.publicKey) ||
+      received.data.tokenId.toNumber() !== tokenId
+    ) {
+      throw new Error("The wrapped mint and its delivery were not reported");
+    }
+    const record = await program.account.nftRecord.fetch(findNftRecordAddress(mint));
+    if (record.originalChain.toNumber() !== DESTINATION_CHAIN_ID) {
+      throw new Error("The NFT record does not point at the origin chain");
+    }
+    // The deposit credited to the `connected` PDA paid for every new account.
+    const connectedAfter = await provider.connection.getBalance(connectedAddress);
+    if (connectedAfter >= connectedBefore + ON_CALL_AMOUNT) {
+      throw new Error("The accounts were not paid from the gateway deposit");
+    }
+  });
+
+  it("Rejects a replayed on_call, even with a padded payload", async () => {
+    const tokenId = 43;
+    const msg: CrossChainMessage = {
+      action: { transferByReference: {} },
+      nonce: new anchor.BN(inboundNonce++),
+      tokenId: new anchor.BN(tokenId),
+      mint: findWrappedMintAddress(DESTINATION_CHAIN_ID, ORIGIN_COLLECTION, tokenId),
+      collection: ORIGIN_COLLECTION,
+      originalChain: new anchor.BN(DESTINATION_CHAIN_ID),
+      destinationChain: new anchor.BN(CHAIN_ID),
+      recipient: Array.from(userKeypair.publicKey.toBytes()),
+      name: "",
+      description: "",
+      image: "",
+      uri: "https://evm.test/nft/43.json",
+      tokens: [],
+    };
+    const accounts = await onCallAccounts(
+      msg,
+      wrappedCollectionMintKeypair.publicKey,
+      userKeypair.publicKey
+    );
+
+    await executeOnCall(encodeMessage(msg), accounts);
+
+    await expectAnchorError(
+      executeOnCall(encodeMessage(msg), accounts),
+      "MessageAlreadyProcessed"
+    );
+    // Decoding ignores trailing bytes, so padding must not yield a new receipt.
+    await expectAnchorError(
+      executeOnCall(Buffer.concat([encodeMessage(msg), Buffer.alloc(8)]), accounts),
+      "MessageAlreadyProcessed"
+    );
+  });
+
+  it("Rejects a transfer from an untrusted sender", async () => {
+    const tokenId = 45;
+    const mint = findWrappedMintAddress(DESTINATION_CHAIN_ID, ORIGIN_COLLECTION, tokenId);
+    const msg: CrossChainMessage = {
+      action: { transferByReference: {} },
+      nonce: new anchor.BN(inboundNonce++),
+      tokenId: new anchor.BN(tokenId),
+      mint,
+      collection: ORIGIN_COLLECTION,
+      originalChain: new anchor.BN(DESTINATION_CHAIN_ID),
+      destinationChain: new anchor.BN(CHAIN_ID),
+      recipient: Array.from(userKeypair.publicKey.toBytes()),
+      name: "",
+      description: "",
+      image: "",
+      uri: "https://evm.test/nft/45.json",
+      tokens: [],
+    };
+
+    await expectAnchorError(
+      executeOnCall(
+        encodeMessage(msg),
+        await onCallAccounts(
+          msg,
+          wrappedCollectionMintKeypair.publicKey,
+          userKeypair.publicKey
+        ),
+        { sender: Array.from(Buffer.alloc(20, 0xee)) }
+      ),
+      "UntrustedSender"
+    );
+    if (await provider.connection.getAccountInfo(mint)) {
+      throw new Error("An untrusted sender had a wrapped NFT minted");
+    }
+  });
+
+  it("Mints a wrapped NFT from an EVM universal NFT message", async () => {
+    const tokenId = 44;
+    const uri = "https://evm.test/nft/44.json";
+    const mint = findWrappedMintAddress(DESTINATION_CHAIN_ID, ORIGIN_COLLECTION, tokenId);
+    // The message the program rebuilds from the ABI payload, keyed by the
+    // nonce of the gateway call delivering it.
+    const { nonce } = await gatewayProgram.account.pda.fetch(gatewayPdaAddress);
+    const msg: CrossChainMessage = {
+      action: { transferByReference: {} },
+      nonce,
+      tokenId: new anchor.BN(tokenId),
+      mint,
+      collection: ORIGIN_COLLECTION,
+      originalChain: new anchor.BN(DESTINATION_CHAIN_ID),
+      destinationChain: new anchor.BN(CHAIN_ID),
+      recipient: Array.from(userKeypair.publicKey.toBytes()),
+      name: "",
+      description: "",
+      image: "",
+      uri,
+      tokens: [],
+    };
+
+    await executeOnCall(
+      encodeEvmMessage(userKeypair.publicKey, tokenId, uri, EVM_SENDER),
+      await onCallAccounts(
+        msg,
+        wrappedCollectionMintKeypair.publicKey,
+        userKeypair.publicKey
+      )
+    );
+
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The wrapped NFT was not minted to its receiver");
+    }
+    const record = await program.account.nftRecord.fetch(findNftRecordAddress(mint));
+    if (record.tokenId.toNumber() !== tokenId) {
+      throw new Error("The NFT record does not carry the EVM token id");
+    }
+  });
+
+  it("Holds a native NFT in escrow while it is away", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+
+    const outboundTransfer = await sendNft(mint, userKeypair);
+
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "0") {
+      throw new Error("The NFT is still in the sender's wallet");
+    }
+    if ((await tokenBalance(mint, pdaAddress)) !== "1") {
+      throw new Error("The NFT was not moved into escrow");
+    }
+    const record = await program.account.nftRecord.fetch(findNftRecordAddress(mint));
+    if (!record.locked || !record.escrowed) {
+      throw new Error("The NFT record is not locked in escrow");
+    }
+
+    // The send is the only transaction that touched the outbound transfer.
+    const [{ signature }] = await provider.connection.getSignaturesForAddress(
+      outboundTransfer,
+      { limit: 1 },
+      "confirmed"
+    );
+    const sent = (await cpiEvents(signature)).find(
+      event => event.name === "crossChainTransferEvent"
+    );
+    if (
+      !sent ||
+      !sent.data.mint.equals(mint) ||
+      sent.data.tokenId.toNumber() !== tokenId ||
+      sent.data.toChain.toNumber() !== DESTINATION_CHAIN_ID ||
+      !Buffer.from(sent.data.recipient).equals(Buffer.from(EVM_RECIPIENT)) ||
+      !sent.data.owner.equals(userKeypair.publicKey) ||
+      !sent.data.escrowed
+    ) {
+      throw new Error("CrossChainTransferEvent was not emitted for the send");
+    }
+  });
+
+  it("Releases a returning native NFT from escrow", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    await sendNft(mint, userKeypair);
+
+    const signature = await receiveNft(returnMessage(mint, tokenId), userKeypair.publicKey);
+
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The NFT was not released to its recipient");
+    }
+    if ((await tokenBalance(mint, pdaAddress)) !== "0") {
+      throw new Error("The NFT is still in escrow");
+    }
+    const events = await cpiEvents(signature);
+    const returned = events.find(event => event.name === "nftReturnedToOrigin");
+    const received = events.find(event => event.name === "nftReceived");
+    if (
+      !returned ||
+      !returned.data.mint.equals(mint) ||
+      !received ||
+      !received.data.mint.equals(mint) ||
+      !received.data.owner.equals(userKeypair.publicKey) ||
+      received.data.tokenId.toNumber() !== tokenId
+    ) {
+      throw new Error("The NFT's return was not reported");
+    }
+    const record = await program.account.nftRecord.fetch(findNftRecordAddress(mint));
+    if (record.locked || record.escrowed) {
+      throw new Error("The NFT record is still locked");
+    }
+  });
+
+  it("Rejects a replayed receive_nft_cross_chain message", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    await sendNft(mint, userKeypair);
+    const msg = returnMessage(mint, tokenId);
+
+    await receiveNft(msg, userKeypair.publicKey);
+
+    await expectAnchorError(
+      receiveNft(msg, userKeypair.publicKey),
+      "MessageAlreadyProcessed"
+    );
+  });
+
+  it("Rejects a message the TSS did not sign", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    await sendNft(mint, userKeypair);
+    const msg = returnMessage(mint, tokenId);
+
+    // A valid TSS signature, but over a different message.
+    await expectAnchorError(
+      receiveNft(
+        msg,
+        userKeypair.publicKey,
+        signTss(inboundMessageHash(CHAIN_ID, returnMessage(mint, tokenId)))
+      ),
+      "TssAuthenticationFailed"
+    );
+    // The right message, signed by a key other than the TSS.
+    await expectAnchorError(
+      receiveNft(
+        msg,
+        userKeypair.publicKey,
+        signTss(inboundMessageHash(CHAIN_ID, msg), Buffer.alloc(32, 9))
+      ),
+      "TssAuthenticationFailed"
+    );
+    // A signature no key could have produced.
+    await expectAnchorError(
+      receiveNft(msg, userKeypair.publicKey, {
+        signature: Array.from(Buffer.alloc(64)),
+        recoveryId: 0,
+      }),
+      "InvalidTssSignature"
+    );
+  });
+
+  it("Only delivers a message to the recipient it names", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    await sendNft(mint, userKeypair);
+    const msg = returnMessage(mint, tokenId);
+    const signature = signTss(inboundMessageHash(CHAIN_ID, msg));
+
+    await expectAnchorError(
+      receiveNft(msg, payer.publicKey, signature),
+      "RecipientMismatch"
+    );
+    // Rewriting the recipient breaks the signature.
+    await expectAnchorError(
+      receiveNft(
+        { ...msg, recipient: Array.from(payer.publicKey.toBytes()) },
+        payer.publicKey,
+        signature
+      ),
+      "TssAuthenticationFailed"
+    );
+
+    await receiveNft(msg, userKeypair.publicKey, signature);
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The NFT was not released to its recipient");
+    }
+  });
+
+  it("Only accepts acknowledgements from the trusted sender", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+    const sent = (await program.account.outboundTransfer.fetch(outboundTransfer)).message;
+    const ack: CrossChainMessage = {
+      ...returnMessage(mint, tokenId),
+      action: { ack: {} },
+      nonce: sent.nonce,
+      destinationChain: new anchor.BN(DESTINATION_CHAIN_ID),
+    };
+    const accounts = {
+      ...(await onCallAccounts(ack, collectionMintKeypair.publicKey, userKeypair.publicKey)),
+      outboundTransfer,
+      userAuthority: userKeypair.publicKey,
+    };
+
+    await expectAnchorError(
+      executeOnCall(encodeMessage(ack), accounts, {
+        sender: Array.from(Buffer.alloc(20, 0xee)),
+      }),
+      "UntrustedSender"
+    );
+
+    await executeOnCall(encodeMessage(ack), accounts);
+    if (await provider.connection.getAccountInfo(outboundTransfer)) {
+      throw new Error("The acknowledged transfer was not closed");
+    }
+  });
+
+  it("Applies the destination config to every outbound transfer", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+
+    // The config set above caps gas amounts at 100,000,000 lamports.
+    await expectAnchorError(
+      sendNft(mint, userKeypair, { gasAmount: new anchor.BN(200_000_000) }),
+      "GasAmountOutOfRange"
+    );
+    // Without a config there are no defaults to fall back to.
+    await expectAnchorError(
+      sendNft(mint, userKeypair, {
+        destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID + 1),
+      }),
+      "AccountNotInitialized"
+    );
+  });
+
+  it("Sends inline metadata within the gateway payload limit", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+
+    // The inline Borsh message fits, and its record is sized to hold it.
+    const outboundTransfer = await sendNft(mint, userKeypair, { byReference: false });
+    const { message } = await program.account.outboundTransfer.fetch(outboundTransfer);
+    if (!("transfer" in message.action) || message.name !== "Traveling NFT") {
+      throw new Error("The NFT was not sent with its metadata inline");
+    }
+
+    // The same metadata as Solidity ABI, with the revert message, exceeds it.
+    await program.methods
+      .setDestinationConfig(
+        new anchor.BN(ABI_DESTINATION_CHAIN_ID),
+        { abi: {} },
+        Array.from(Buffer.alloc(20, 0x22)),
+        new anchor.BN(1_000_000),
+        new anchor.BN(1_000_000),
+        new anchor.BN(100_000_000),
+        EVM_SENDER,
+        Array.from(Buffer.alloc(20, 0x44)),
+        Array.from(Buffer.alloc(20, 0x55))
+      )
+      .accountsPartial({
+        authority: authorityKeypair.publicKey,
+        pda: pdaAddress,
+        destinationConfig: findDestinationConfigAddress(ABI_DESTINATION_CHAIN_ID),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authorityKeypair])
+      .rpc();
+    const { mint: abiMint } = await mintNativeNft(userKeypair.publicKey);
+
+    await expectAnchorError(
+      sendNft(abiMint, userKeypair, {
+        byReference: false,
+        destinationChain: ABI_DESTINATION_CHAIN_ID,
+      }),
+      "MessageTooLarge"
+    );
+  });
+
+  it("Restores a reverted transfer and emits CrossChainTransferReverted", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+    const { nonce } = (await program.account.outboundTransfer.fetch(outboundTransfer))
+      .message;
+    const accounts = await onRevertAccounts(mint, outboundTransfer, userKeypair.publicKey);
+    const data = await revertMessage(outboundTransfer);
+
+    const signature = await executeOnRevert(data, accounts, {
+      sender: userKeypair.publicKey,
+    });
+
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The reverted NFT was not restored to its sender");
+    }
+    if (await provider.connection.getAccountInfo(outboundTransfer)) {
+      throw new Error("The reverted transfer was not closed");
+    }
+    const reverted = (await cpiEvents(signature)).find(
+      event => event.name === "crossChainTransferReverted"
+    );
+    if (
+      !reverted ||
+      !reverted.data.mint.equals(mint) ||
+      reverted.data.tokenId.toNumber() !== tokenId ||
+      !reverted.data.nonce.eq(nonce) ||
+      !reverted.data.user.equals(userKeypair.publicKey)
+    ) {
+      throw new Error("CrossChainTransferReverted was not emitted for the transfer");
+    }
+
+    // The completed transfer is closed, so it cannot be reverted again.
+    await expectAnchorError(
+      executeOnRevert(data, accounts, { sender: userKeypair.publicKey }),
+      "AccountNotInitialized"
+    );
+  });
+
+  it("Rejects a revert that does not match the transfer", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+    const accounts = await onRevertAccounts(mint, outboundTransfer, userKeypair.publicKey);
+    const sender = userKeypair.publicKey;
+
+    await expectAnchorError(
+      executeOnRevert(
+        await revertMessage(outboundTransfer, { mint: Keypair.generate().publicKey }),
+        accounts,
+        { sender }
+      ),
+      "OutboundTransferMismatch"
+    );
+    await expectAnchorError(
+      executeOnRevert(
+        await revertMessage(outboundTransfer, { tokenId: new anchor.BN(tokenId + 1) }),
+        accounts,
+        { sender }
+      ),
+      "OutboundTransferMismatch"
+    );
+    await expectAnchorError(
+      executeOnRevert(
+        await revertMessage(outboundTransfer, { user: payer.publicKey }),
+        accounts,
+        { sender }
+      ),
+      "OutboundTransferMismatch"
+    );
+    // A bare revert message without its envelope is not decoded.
+    await expectAnchorError(
+      executeOnRevert((await revertMessage(outboundTransfer)).subarray(6), accounts, {
+        sender,
+      }),
+      "InvalidMessage"
+    );
+    // The depositor the gateway reports must be the sender as well.
+    await expectAnchorError(
+      executeOnRevert(await revertMessage(outboundTransfer), accounts, {
+        sender: payer.publicKey,
+      }),
+      "OutboundTransferMismatch"
+    );
+  });
+
+  it("Rejects on_revert called directly rather than by the gateway", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+
+    await expectAnchorError(
+      program.methods
+        .onRevert(
+          new anchor.BN(0),
+          userKeypair.publicKey,
+          await revertMessage(outboundTransfer)
+        )
+        .accountsPartial(
+          await onRevertAccounts(mint, outboundTransfer, userKeypair.publicKey)
+        )
+        .rpc(),
+      "InvalidCaller"
+    );
+  });
+
+  it("Sends the same NFT again once its transfers are reverted or delivered", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+    const userBalance = () => provider.connection.getBalance(userKeypair.publicKey);
+
+    // A reverted transfer is closed and its rent returned to the sender.
+    const reverted = await sendNft(mint, userKeypair);
+    const revertedRent = await provider.connection.getBalance(reverted);
+    let balanceBefore = await userBalance();
+    await executeOnRevert(
+      await revertMessage(reverted),
+      await onRevertAccounts(mint, reverted, userKeypair.publicKey),
+      { sender: userKeypair.publicKey }
+    );
+    if ((await userBalance()) - balanceBefore !== revertedRent) {
+      throw new Error("The reverted transfer's rent was not returned to its sender");
+    }
+
+    // So is a delivered one, once its acknowledgement arrives.
+    const delivered = await sendNft(mint, userKeypair);
+    if (delivered.equals(reverted)) {
+      throw new Error("The second transfer reused the first transfer's record");
+    }
+    const sent = (await program.account.outboundTransfer.fetch(delivered)).message;
+    const ack: CrossChainMessage = {
+      ...returnMessage(mint, tokenId),
+      action: { ack: {} },
+      nonce: sent.nonce,
+      destinationChain: new anchor.BN(DESTINATION_CHAIN_ID),
+    };
+    const deliveredRent = await provider.connection.getBalance(delivered);
+    balanceBefore = await userBalance();
+    await executeOnCall(encodeMessage(ack), {
+      ...(await onCallAccounts(ack, collectionMintKeypair.publicKey, userKeypair.publicKey)),
+      outboundTransfer: delivered,
+      userAuthority: userKeypair.publicKey,
+      destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID),
+    });
+    if (await provider.connection.getAccountInfo(delivered)) {
+      throw new Error("The delivered transfer was not closed");
+    }
+    if ((await userBalance()) - balanceBefore !== deliveredRent) {
+      throw new Error("The delivered transfer's rent was not returned to its sender");
+    }
+
+    // Back home, the NFT can leave once more.
+    await receiveNft(returnMessage(mint, tokenId), userKeypair.publicKey);
+    await sendNft(mint, userKeypair);
+    if ((await tokenBalance(mint, pdaAddress)) !== "1") {
+      throw new Error("The NFT was not escrowed for its third transfer");
+    }
+  });
+
+  const setReclaimTimeout = (reclaimTimeout: number) =>
+    program.methods
+      .setReclaimTimeout(new anchor.BN(reclaimTimeout))
+      .accountsPartial({ authority: authorityKeypair.publicKey, pda: pdaAddress })
+      .signers([authorityKeypair])
+      .rpc();
+
+  const setPaused = (paused: boolean) =>
+    program.methods
+      .setPaused(paused)
+      .accountsPartial({ authority: authorityKeypair.publicKey, pda: pdaAddress })
+      .signers([authorityKeypair])
+      .rpc();
+
+  // Sends the native NFTs `mints` of `owner` as one batch and returns its
+  // outbound transfer.
+  const sendBatch = async (mints: PublicKey[], owner: Keypair) => {
+    const { nonce } = await program.account.universalNftPda.fetch(pdaAddress);
+    const outboundTransfer = findOutboundTransferAddress(nonce);
+    const remainingAccounts = [];
+    for (const mint of mints) {
+      for (const pubkey of [
+        mint,
+        findNftRecordAddress(mint),
+        await getAssociatedTokenAddress(mint, owner.publicKey),
+        await getAssociatedTokenAddress(mint, pdaAddress, true),
+      ]) {
+        remainingAccounts.push({ pubkey, isSigner: false, isWritable: true });
+      }
+    }
+
+    await program.methods
+      .sendBatchCrossChain(new anchor.BN(DESTINATION_CHAIN_ID), EVM_RECIPIENT, null, null)
+      .accountsPartial({
+        user: owner.publicKey,
+        pda: pdaAddress,
+        collection: collectionAddress,
+        collectionMint: collectionMintKeypair.publicKey,
+        outboundTransfer,
+        destinationConfig: findDestinationConfigAddress(DESTINATION_CHAIN_ID),
+        gatewayPda: gatewayPdaAddress,
+        gatewayProgram: gatewayProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers([owner])
+      .rpc();
+
+    return outboundTransfer;
+  };
+
+  // Reclaims the transfer at `outboundTransfer` of native NFT `mint` for
+  // `owner`, proven by the `authority` co-signing or by a TSS `proof`. The
+  // further NFTs of a batch are passed as `batchMints`.
+  const reclaimNft = async (
+    mint: PublicKey,
+    outboundTransfer: PublicKey,
+    owner: Keypair,
+    {
+      authority = null as Keypair | null,
+      proof = null as ReturnType<typeof signTss> | null,
+      batchMints = [] as PublicKey[],
+    } = {}
+  ) => {
+    const remainingAccounts = [];
+    for (const batchMint of batchMints) {
+      for (const pubkey of [
+        findNftRecordAddress(batchMint),
+        batchMint,
+        await getAssociatedTokenAddress(batchMint, owner.publicKey),
+        await getAssociatedTokenAddress(batchMint, pdaAddress, true),
+        findMetadataAddress(batchMint),
+        findMasterEditionAddress(batchMint),
+      ]) {
+        remainingAccounts.push({ pubkey, isSigner: false, isWritable: true });
+      }
+    }
+
+    return program.methods
+      .reclaimStuckTransfer(proof ? proof.signature : null, proof ? proof.recoveryId : 0)
+      .accountsPartial({
+        user: owner.publicKey,
+        authority: authority ? authority.publicKey : null,
+        pda: pdaAddress,
+        outboundTransfer,
+        nftRecord: findNftRecordAddress(mint),
+        nftMint: mint,
+        userTokenAccount: await getAssociatedTokenAddress(mint, owner.publicKey),
+        escrowTokenAccount: await getAssociatedTokenAddress(mint, pdaAddress, true),
+        metadata: findMetadataAddress(mint),
+        masterEdition: findMasterEditionAddress(mint),
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(remainingAccounts)
+      .signers(authority ? [owner, authority] : [owner])
+      .rpc();
+  };
+
+  it("Only lets a stuck transfer be reclaimed once the timeout has passed", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+
+    // The default timeout is 7 days.
+    await expectAnchorError(
+      reclaimNft(mint, outboundTransfer, userKeypair, { authority: authorityKeypair }),
+      "ReclaimTimeoutNotElapsed"
+    );
+
+    await setReclaimTimeout(0);
+    try {
+      await reclaimNft(mint, outboundTransfer, userKeypair, { authority: authorityKeypair });
+    } finally {
+      await setReclaimTimeout(7 * 24 * 60 * 60);
+    }
+    if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+      throw new Error("The reclaimed NFT was not restored to its sender");
+    }
+  });
+
+  it("Reclaims a stuck transfer on a TSS proof and closes it to the sender", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+    const sent = (await program.account.outboundTransfer.fetch(outboundTransfer))
+      .message as CrossChainMessage;
+    await setReclaimTimeout(0);
+
+    try {
+      // Without the authority, the sender needs the TSS's proof.
+      await expectAnchorError(
+        reclaimNft(mint, outboundTransfer, userKeypair),
+        "ReclaimProofRequired"
+      );
+      await expectAnchorError(
+        reclaimNft(mint, outboundTransfer, userKeypair, {
+          proof: signTss(reclaimProofHash(CHAIN_ID, sent), Buffer.alloc(32, 9)),
+        }),
+        "TssAuthenticationFailed"
+      );
+      // A proof covers only the message it was signed for.
+      await expectAnchorError(
+        reclaimNft(mint, outboundTransfer, userKeypair, {
+          proof: signTss(
+            reclaimProofHash(CHAIN_ID, { ...sent, tokenId: sent.tokenId.addn(1) })
+          ),
+        }),
+        "TssAuthenticationFailed"
+      );
+
+      const rent = await provider.connection.getBalance(outboundTransfer);
+      const balanceBefore = await provider.connection.getBalance(userKeypair.publicKey);
+      await reclaimNft(mint, outboundTransfer, userKeypair, {
+        proof: signTss(reclaimProofHash(CHAIN_ID, sent)),
+      });
+
+      if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+        throw new Error("The reclaimed NFT was not restored to its sender");
+      }
+      if (await provider.connection.getAccountInfo(outboundTransfer)) {
+        throw new Error("The reclaimed transfer was not closed");
+      }
+      const balanceAfter = await provider.connection.getBalance(userKeypair.publicKey);
+      if (balanceAfter - balanceBefore !== rent) {
+        throw new Error("The outbound transfer's rent was not returned to its sender");
+      }
+    } finally {
+      await setReclaimTimeout(7 * 24 * 60 * 60);
+    }
+  });
+
+  it("Restores every NFT of a reclaimed batch", async () => {
+    const first = await mintNativeNft(userKeypair.publicKey);
+    const second = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendBatch([first.mint, second.mint], userKeypair);
+    await setReclaimTimeout(0);
+
+    try {
+      await reclaimNft(first.mint, outboundTransfer, userKeypair, {
+        authority: authorityKeypair,
+        batchMints: [second.mint],
+      });
+    } finally {
+      await setReclaimTimeout(7 * 24 * 60 * 60);
+    }
+
+    for (const { mint } of [first, second]) {
+      if ((await tokenBalance(mint, userKeypair.publicKey)) !== "1") {
+        throw new Error("An NFT of the reclaimed batch was not restored to its sender");
+      }
+      if ((await tokenBalance(mint, pdaAddress)) !== "0") {
+        throw new Error("An NFT of the reclaimed batch was left in escrow");
+      }
+    }
+    if (await provider.connection.getAccountInfo(outboundTransfer)) {
+      throw new Error("The reclaimed batch transfer was not closed");
+    }
+  });
+
+  it("Holds reclaims while the program is paused", async () => {
+    const { mint } = await mintNativeNft(userKeypair.publicKey);
+    const outboundTransfer = await sendNft(mint, userKeypair);
+    await setReclaimTimeout(0);
+    await setPaused(true);
+
+    try {
+      await expectAnchorError(
+        reclaimNft(mint, outboundTransfer, userKeypair, { authority: authorityKeypair }),
+        "ProgramPaused"
+      );
+    } finally {
+      await setPaused(false);
+      await setReclaimTimeout(7 * 24 * 60 * 60);
+    }
+  });
+
+  it("Emits events as inner instructions rather than logs", async () => {
+    const signature = await setPaused(true);
+    await setPaused(false);
+
+    const [event] = await cpiEvents(signature);
+    if (
+      !event ||
+      event.name !== "pausedUpdated" ||
+      event.data.paused !== true ||
+      !event.data.authority.equals(authorityKeypair.publicKey)
+    ) {
+      throw new Error("PausedUpdated was not emitted as an inner instruction");
+    }
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    if (tx!.meta!.logMessages!.some(log => log.startsWith("Program data: "))) {
+      throw new Error("The event was also written to the logs");
+    }
+  });
+
+  it("Rejects the return of a native NFT that is not in escrow", async () => {
+    const { mint, tokenId } = await mintNativeNft(userKeypair.publicKey);
+
+    await expectAnchorError(
+      receiveNft(returnMessage(mint, tokenId), userKeypair.publicKey),
+      "NftNotEscrowed"
+    );
   });
 
   it("Demo complete - ready for cross-chain!", async () => {
@@ -112,7 +2174,9 @@ describe("universal-nft", () => {
     console.log(`Program ID: ${program.programId.toString()}`);
     console.log(`Chain ID: ${CHAIN_ID}`);
     console.log("\n✅ Implemented features:");
-    console.log("  • NFT minting with cross-chain metadata");
+    console.log("  • NFT minting with Metaplex metadata and master edition");
+    console.log("  • Verified on-chain collection membership");
+    console.log("  • Token-2022 NFTs with embedded and non-transferable metadata");
     console.log("  • Cross-chain transfer preparation (burn & lock)");
     console.log("  • Incoming NFT reception and wrapping");
     console.log("  • Admin controls and security");